@@ -1,25 +1,277 @@
+use crate::types::{Matrix, VectorFunction};
+use super::helpers::RichardsonTableau;
+
+/**
+ * The one-sided differences below use h = sqrt(eps) * max(1, |x|), the central and the second order
+ * forward (Taylor) differences h = cbrt(eps) * max(1, |x|), which balance truncation and rounding errors
+ */
 pub fn derivative(f: fn(f64) -> f64, x: f64) -> f64
 {
-    let delta_x = 1e-10;
+    let delta_x = auto_step(x, 2.);
     (f(x + delta_x) - f(x)) / delta_x
 }
 
 pub fn df_central(f: fn(f64) -> f64, x: f64) -> f64 {
-    let h = 1e-5;
+    let h = auto_step(x, 3.);
     (f(x + h) - f(x - h)) / (2. * h)
 }
 
 pub fn df_successor(f: fn(f64) -> f64, x: f64) -> f64 {
-    let h = 1e-10;
+    let h = auto_step(x, 2.);
     (f(x) - f(x - h)) / h
 }
 
 pub fn df_progressive(f: fn(f64) -> f64, x: f64) -> f64 {
-    let h = 1e-10;
+    let h = auto_step(x, 2.);
     (f(x + h) - f(x)) / h
 }
 
 pub fn df_taylor(f: fn(f64) -> f64, x: f64) -> f64 {
-    let h = 1e-10;
+    let h = auto_step(x, 3.);
     ((-3.) * f(x) + 4. * f(x + h) - f(x + 2. * h)) / (2. * h)
 }
+
+
+/**
+ * Central finite differences of configurable order for the first derivative
+ * order is the order of the truncation error O(h^order), with an order + 1 point stencil: 2, 4, 6 or 8
+ * step = None chooses h = eps^(1 / (order + 1)) * max(1, |x|), the step that balances the
+ * O(h^order) truncation error against the O(eps / h) rounding error
+ * The fields are only set through new and with_step, which validate them
+ */
+pub struct FiniteDiff {
+    order: usize,
+    step: Option<f64>,
+}
+
+impl Default for FiniteDiff {
+    fn default() -> Self {
+        Self { order: 2, step: None }
+    }
+}
+
+impl FiniteDiff {
+    pub fn new(order: usize) -> Self {
+        assert!(matches!(order, 2 | 4 | 6 | 8), "central differences of order {} are not available, use 2, 4, 6 or 8", order);
+
+        Self { order, ..Self::default() }
+    }
+
+    /**
+     * Fixed step h, which must be positive and finite
+     */
+    pub fn with_step(order: usize, step: f64) -> Self {
+        assert!(step > 0. && step.is_finite(), "the step must be positive and finite, got {}", step);
+
+        Self { step: Some(step), ..Self::new(order) }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn step(&self) -> Option<f64> {
+        self.step
+    }
+
+    /**
+     * The step used at x, rounded so that x + h - x == h exactly
+     */
+    pub fn step_at(&self, x: f64) -> f64 {
+        match self.step {
+            Some(h) => h,
+            None => auto_step(x, self.order as f64 + 1.),
+        }
+    }
+
+    /**
+     * Returns (derivative, error_estimate)
+     * D(h) = sum( c_k ( f(x + k h) - f(x - k h) ) ) / h
+     * The truncation error is estimated by Richardson with the same stencil at 2h, |D(h) - D(2h)| / (2^order - 1),
+     * the rounding error by eps * sum( |c_k| ( |f(x + k h)| + |f(x - k h)| ) ) / h
+     */
+    pub fn derivative(&self, f: fn(f64) -> f64, x: f64) -> (f64, f64) {
+        let h = self.step_at(x);
+        let (value, rounding) = self.stencil(f, x, h);
+        let (value_2h, _) = self.stencil(f, x, 2. * h);
+
+        let truncation = f64::abs(value - value_2h) / ( 2f64.powi(self.order as i32) - 1. );
+
+        (value, truncation + rounding)
+    }
+
+    /**
+     * Returns D(h) and the estimate of its rounding error
+     */
+    pub(crate) fn stencil(&self, f: fn(f64) -> f64, x: f64, h: f64) -> (f64, f64) {
+        let coefficients = central_coefficients(self.order);
+        let mut sum = 0.;
+        let mut magnitude = 0.;
+
+        for (k, c) in coefficients.iter().enumerate() {
+            let offset = ( k + 1 ) as f64 * h;
+            let (forward, backward) = (f(x + offset), f(x - offset));
+            sum += c * ( forward - backward );
+            magnitude += f64::abs(*c) * ( f64::abs(forward) + f64::abs(backward) );
+        }
+
+        (sum / h, f64::EPSILON * magnitude / h)
+    }
+}
+
+
+/**
+ * Ridders' method: central differences D(h_k), h_k = h_0 / 1.4^k, extrapolated to h = 0 with a RichardsonTableau
+ * The error of R[k][j] is estimated by max( |R[k][j] - R[k][j - 1]|, |R[k][j] - R[k - 1][j - 1]| ),
+ * the element with the smallest error is returned, and the iteration stops once the diagonal
+ * moves away from it by more than twice that error (rounding errors take over)
+ * initial_step = None uses h_0 = 0.1 * max(1, |x|), h_0 should be large rather than small
+ * Returns (derivative, error_estimate)
+ */
+pub fn ridders(f: fn(f64) -> f64, x: f64, initial_step: Option<f64>) -> (f64, f64) {
+    let mut h = initial_step.unwrap_or(0.1 * f64::max(1., f64::abs(x)));
+    let central = FiniteDiff::new(2);
+    let mut tableau = RichardsonTableau::new(RIDDERS_RATIO, 2., 2.);
+
+    tableau.push(central.stencil(f, x, h).0);
+    let mut best = (tableau.value(), f64::INFINITY);
+
+    for k in 1..RIDDERS_MAX_ROWS {
+        h /= RIDDERS_RATIO;
+        tableau.push(central.stencil(f, x, h).0);
+
+        let (row, previous) = (&tableau.rows[k], &tableau.rows[k - 1]);
+
+        for j in 1..=k {
+            let error = f64::max(f64::abs(row[j] - row[j - 1]), f64::abs(row[j] - previous[j - 1]));
+
+            if error <= best.1 {
+                best = (row[j], error);
+            }
+        }
+
+        if f64::abs(row[k] - previous[k - 1]) >= 2. * best.1 {
+            break;
+        }
+    }
+
+    best
+}
+
+
+const RIDDERS_RATIO: f64 = 1.4;
+const RIDDERS_MAX_ROWS: usize = 10;
+
+
+/**
+ * Coefficients c_k, k = 1..order / 2, of the central difference f'(x) ~ sum( c_k ( f(x + k h) - f(x - k h) ) ) / h
+ */
+fn central_coefficients(order: usize) -> &'static [f64] {
+    match order {
+        2 => &[1. / 2.],
+        4 => &[2. / 3., -1. / 12.],
+        6 => &[3. / 4., -3. / 20., 1. / 60.],
+        8 => &[4. / 5., -1. / 5., 4. / 105., -1. / 280.],
+        _ => panic!("central differences of order {} are not available, use 2, 4, 6 or 8", order),
+    }
+}
+
+
+/**
+ * h = eps^(1 / root) * max(1, |x|), adjusted so that it is exactly representable as (x + h) - x
+ */
+fn auto_step(x: f64, root: f64) -> f64 {
+    let h = f64::EPSILON.powf(1. / root) * f64::max(1., f64::abs(x));
+    ( x + h ) - x
+}
+
+/**
+ * Approximates the Jacobian matrix of a vector function f: R^n -> R^m at the point x
+ * using forward differences, one column per variable
+ * J[i][j] = ( f_i( x + h * e_j ) - f_i( x ) ) / h, h = sqrt(eps) * max(1, |x_j|)
+ */
+pub fn jacobian(f: &VectorFunction, x: &[f64]) -> Matrix<f64>
+{
+    let fx = f(x);
+    let m = fx.len();
+    let n = x.len();
+    let mut j = vec![vec![0.0; n]; m];
+    let mut x_h = x.to_vec();
+
+    for col in 0..n {
+        let h = f64::EPSILON.sqrt() * f64::max(1., f64::abs(x[col]));
+        x_h[col] = x[col] + h;
+        let fx_h = f(&x_h);
+        x_h[col] = x[col];
+
+        for (row, line) in j.iter_mut().enumerate() {
+            line[col] = (fx_h[row] - fx[row]) / h;
+        }
+    }
+
+    j
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finite_diff_stencils_converge_with_their_order() {
+        let x = 1.;
+        let exact = f64::cos(x);
+
+        for order in [2, 4, 6, 8] {
+            let finite_diff = FiniteDiff::new(order);
+            let errors: Vec<f64> = [0.4, 0.2, 0.1].iter().map(|h| f64::abs(finite_diff.stencil(f64::sin, x, *h).0 - exact)).collect();
+
+            for pair in errors.windows(2) {
+                let observed_order = f64::log2(pair[0] / pair[1]);
+                assert!(f64::abs(observed_order - order as f64) < 0.1, "order {order}: observed {observed_order}");
+            }
+        }
+    }
+
+    #[test]
+    fn finite_diff_error_estimate_bounds_the_error() {
+        for order in [2, 4, 6, 8] {
+            let (value, error) = FiniteDiff::new(order).derivative(f64::exp, 0.5);
+
+            assert!(f64::abs(value - f64::exp(0.5)) <= error);
+            assert!(error < 1e-6);
+        }
+    }
+
+    #[test]
+    fn ridders_reaches_near_machine_precision_with_a_bounding_error_estimate() {
+        for (f, x, exact) in [(f64::sin as fn(f64) -> f64, 1., f64::cos(1.)), (f64::exp, 0.5, f64::exp(0.5))] {
+            let (value, error) = ridders(f, x, None);
+
+            assert!(f64::abs(value - exact) < 1e-12, "f'({x}) = {value} instead of {exact}");
+            assert!(f64::abs(value - exact) <= error && error < 1e-11, "error estimate {error}");
+        }
+    }
+
+    #[test]
+    fn finite_diff_with_step_uses_the_fixed_step() {
+        let finite_diff = FiniteDiff::with_step(4, 0.1);
+
+        assert_eq!((finite_diff.order(), finite_diff.step()), (4, Some(0.1)));
+        assert_eq!(finite_diff.step_at(100.), 0.1);
+        assert_eq!(finite_diff.derivative(f64::sin, 1.).0, finite_diff.stencil(f64::sin, 1., 0.1).0);
+        assert_eq!(FiniteDiff::default().step(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive and finite")]
+    fn finite_diff_rejects_a_nonpositive_step() {
+        FiniteDiff::with_step(2, 0.);
+    }
+
+    #[test]
+    #[should_panic(expected = "order 3")]
+    fn finite_diff_rejects_unavailable_orders() {
+        FiniteDiff::new(3);
+    }
+}
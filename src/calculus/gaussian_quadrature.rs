@@ -0,0 +1,189 @@
+use crate::linear_algebra::eigen::{symmetric_eigen, EigenOptions};
+use crate::types::Matrix;
+
+
+/**
+ * Quadrature rule sum( weights[i] * f( nodes[i] ) ) approximating the integral of w(x) f(x)
+ * The n point Gaussian rules are exact for polynomials f of degree up to 2n - 1
+ */
+#[derive(Clone, Debug)]
+pub struct QuadratureRule {
+    pub nodes: Vec<f64>,
+    pub weights: Vec<f64>,
+}
+
+
+impl QuadratureRule {
+    /**
+     * Gauss-Legendre rule on [-1, 1], w(x) = 1
+     * Jacobi matrix: alpha_k = 0, beta_k = k / sqrt( 4k² - 1 )
+     */
+    pub fn gauss_legendre(n: usize) -> Self {
+        let off_diagonal: Vec<f64> = (1..n).map(|k| k as f64 / f64::sqrt(4. * (k * k) as f64 - 1.)).collect();
+        golub_welsch(&vec![0.; n], &off_diagonal, 2.)
+    }
+
+    /**
+     * Gauss-Laguerre rule on [0, inf), w(x) = e^-x
+     * Jacobi matrix: alpha_k = 2k + 1, beta_k = k
+     */
+    pub fn gauss_laguerre(n: usize) -> Self {
+        let diagonal: Vec<f64> = (0..n).map(|k| 2. * k as f64 + 1.).collect();
+        let off_diagonal: Vec<f64> = (1..n).map(|k| k as f64).collect();
+        golub_welsch(&diagonal, &off_diagonal, 1.)
+    }
+
+    /**
+     * Gauss-Hermite rule on (-inf, inf), w(x) = e^-x²
+     * Jacobi matrix: alpha_k = 0, beta_k = sqrt( k / 2 )
+     */
+    pub fn gauss_hermite(n: usize) -> Self {
+        let off_diagonal: Vec<f64> = (1..n).map(|k| f64::sqrt(k as f64 / 2.)).collect();
+        golub_welsch(&vec![0.; n], &off_diagonal, f64::sqrt(std::f64::consts::PI))
+    }
+
+    /**
+     * Gauss-Chebyshev rule (first kind) on [-1, 1], w(x) = 1 / sqrt( 1 - x² )
+     * x_i = cos( (2i - 1) pi / 2n ), w_i = pi / n
+     */
+    pub fn gauss_chebyshev(n: usize) -> Self {
+        let pi = std::f64::consts::PI;
+        let nodes = (1..=n).rev().map(|i| f64::cos(( 2. * i as f64 - 1. ) * pi / ( 2. * n as f64 ))).collect();
+        let weights = vec![pi / n as f64; n];
+
+        Self { nodes, weights }
+    }
+
+    /**
+     * Applies the rule: sum( weights[i] * f( nodes[i] ) )
+     */
+    pub fn integrate(&self, f: &dyn Fn(f64) -> f64) -> f64 {
+        self.nodes.iter().zip(&self.weights).map(|(x, w)| w * f( *x )).sum()
+    }
+
+    /**
+     * Applies a rule on [-1, 1] to [a, b] with the change of variables x = (a + b) / 2 + (b - a) / 2 * t
+     */
+    pub fn integrate_interval(&self, f: &dyn Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+        let center = ( a + b ) / 2.;
+        let half_length = ( b - a ) / 2.;
+
+        half_length * self.integrate(&|t| f( center + half_length * t ))
+    }
+}
+
+
+/**
+ * Golub-Welsch algorithm
+ * The nodes are the eigenvalues of the symmetric tridiagonal Jacobi matrix of the orthogonal polynomials,
+ * weights[i] = mu0 * v_i[0]², with v_i the normalized eigenvector and mu0 the integral of the weight function
+ * Panics if the eigenvalue solver does not converge
+ */
+fn golub_welsch(diagonal: &[f64], off_diagonal: &[f64], mu0: f64) -> QuadratureRule {
+    let n = diagonal.len();
+    let mut jacobi_matrix: Matrix<f64> = vec![vec![0.; n]; n];
+
+    for (i, row) in jacobi_matrix.iter_mut().enumerate() {
+        row[i] = diagonal[i];
+
+        if i > 0 {
+            row[i - 1] = off_diagonal[i - 1];
+        }
+
+        if i + 1 < n {
+            row[i + 1] = off_diagonal[i];
+        }
+    }
+
+    let options = EigenOptions { tolerance: f64::EPSILON, ..EigenOptions::default() };
+    let eigen = symmetric_eigen(&jacobi_matrix, &options);
+    assert!(eigen.converged, "the eigenvalues of the {} x {} Jacobi matrix did not converge", n, n);
+
+    let weights = eigen.eigenvectors.first().map_or(vec![], |first_row| first_row.iter().map(|v| mu0 * v * v).collect());
+
+    QuadratureRule { nodes: eigen.eigenvalues, weights }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /**
+     * (k - 1)!! / k!! for an even k, the ratio in the even moments of the Hermite and Chebyshev weights
+     */
+    fn double_factorial_ratio(k: usize) -> f64 {
+        (1..=k / 2).map(|i| ( 2 * i - 1 ) as f64 / ( 2 * i ) as f64).product()
+    }
+
+    /**
+     * Checks sum( w_i x_i^k ) against the moments of the weight function for k <= 2n - 1
+     */
+    fn assert_exact_for_monomials(rule: fn(usize) -> QuadratureRule, moment: fn(usize) -> f64) {
+        for n in 1..=10 {
+            let quadrature = rule(n);
+
+            assert_eq!((quadrature.nodes.len(), quadrature.weights.len()), (n, n));
+
+            for k in 0..2 * n {
+                let value = quadrature.integrate(&|x| x.powi(k as i32));
+                let exact = moment(k);
+                let scale = (0..=k).map(moment).fold(1., |acc: f64, m| acc.max(f64::abs(m)));
+
+                assert!(f64::abs(value - exact) <= 1e-13 * scale, "n = {n}, k = {k}: {value} instead of {exact}");
+            }
+        }
+    }
+
+    #[test]
+    fn gauss_legendre_is_exact_up_to_degree_2n_minus_1() {
+        assert_exact_for_monomials(QuadratureRule::gauss_legendre, |k| if k % 2 == 0 { 2. / ( k + 1 ) as f64 } else { 0. });
+    }
+
+    #[test]
+    fn gauss_laguerre_is_exact_up_to_degree_2n_minus_1() {
+        // integral of x^k e^-x over [0, inf) is k!
+        assert_exact_for_monomials(QuadratureRule::gauss_laguerre, |k| (1..=k).map(|i| i as f64).product());
+    }
+
+    #[test]
+    fn gauss_hermite_is_exact_up_to_degree_2n_minus_1() {
+        // integral of x^k e^-x² is Gamma( (k + 1) / 2 ) = sqrt(pi) (k - 1)!! / 2^(k / 2) for an even k
+        assert_exact_for_monomials(QuadratureRule::gauss_hermite, |k| {
+            if k % 2 == 0 { PI.sqrt() * (1..k).step_by(2).map(|i| i as f64).product::<f64>() / 2f64.powi(k as i32 / 2) } else { 0. }
+        });
+    }
+
+    #[test]
+    fn gauss_chebyshev_is_exact_up_to_degree_2n_minus_1() {
+        // integral of x^k / sqrt( 1 - x² ) over [-1, 1] is pi (k - 1)!! / k!! for an even k
+        assert_exact_for_monomials(QuadratureRule::gauss_chebyshev, |k| if k % 2 == 0 { PI * double_factorial_ratio(k) } else { 0. });
+    }
+
+    #[test]
+    fn gauss_legendre_matches_the_published_nodes_and_weights() {
+        let rule = QuadratureRule::gauss_legendre(5);
+        let nodes = [-0.906_179_845_938_664, -0.538_469_310_105_683_1, 0., 0.538_469_310_105_683_1, 0.906_179_845_938_664];
+        let weights = [0.236_926_885_056_189_1, 0.478_628_670_499_366_5, 0.568_888_888_888_888_9, 0.478_628_670_499_366_5, 0.236_926_885_056_189_1];
+
+        for (x, expected) in rule.nodes.iter().zip(nodes) {
+            assert!(f64::abs(x - expected) < 1e-15);
+        }
+
+        for (w, expected) in rule.weights.iter().zip(weights) {
+            assert!(f64::abs(w - expected) < 1e-15);
+        }
+
+        let two_points = QuadratureRule::gauss_legendre(2);
+        assert!(f64::abs(two_points.nodes[1] - 1. / 3f64.sqrt()) < 1e-15 && f64::abs(two_points.weights[0] - 1.) < 1e-15);
+    }
+
+    #[test]
+    fn integrate_interval_maps_the_legendre_rule() {
+        let rule = QuadratureRule::gauss_legendre(6);
+        let value = rule.integrate_interval(&|x| x.powi(11) - 3. * x * x, 1., 2.);
+
+        assert!(f64::abs(value - ( ( 4096. - 1. ) / 12. - 7. )) < 1e-11);
+    }
+}
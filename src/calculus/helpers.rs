@@ -17,3 +17,128 @@ pub fn relative_error(a: f64, b: f64) -> f64
 {
     f64::abs(b - a) / f64::abs(b)
 }
+
+
+/**
+ * Neumaier's compensated summation (improved Kahan-Babuska)
+ * The rounding error of every addition is accumulated apart and added back at the end,
+ * so the error of the total does not grow with the number of terms
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NeumaierSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl NeumaierSum {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, x: f64) {
+        let t = self.sum + x;
+
+        if f64::abs(self.sum) >= f64::abs(x) {
+            self.compensation += ( self.sum - t ) + x;
+        } else {
+            self.compensation += ( x - t ) + self.sum;
+        }
+
+        self.sum = t;
+    }
+
+    pub fn total(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+
+/**
+ * Sums the values with NeumaierSum
+ */
+pub fn compensated_sum<I: IntoIterator<Item = f64>>(values: I) -> f64 {
+    let mut sum = NeumaierSum::new();
+    values.into_iter().for_each(|x| sum.add(x));
+    sum.total()
+}
+
+
+/**
+ * Richardson extrapolation tableau
+ * A(h) = A + c_1 h^p_1 + c_2 h^p_2 + ... is sampled at h_k = h_0 / ratio^k, p_j = leading_order + (j - 1) * order_step
+ * R[k][0] = A(h_k), R[k][j] = R[k][j - 1] + ( R[k][j - 1] - R[k - 1][j - 1] ) / ( ratio^p_j - 1 )
+ * removes one more term of the expansion at every column
+ * Romberg integration is ratio = 2, leading_order = order_step = 2 on the trapezoid rule,
+ * Ridders' method ratio = 1.4 on central differences
+ */
+pub struct RichardsonTableau {
+    pub ratio: f64,
+    pub leading_order: f64,
+    pub order_step: f64,
+    pub rows: Vec<Vec<f64>>,
+}
+
+impl RichardsonTableau {
+    pub fn new(ratio: f64, leading_order: f64, order_step: f64) -> Self {
+        Self { ratio, leading_order, order_step, rows: vec![] }
+    }
+
+    /**
+     * Adds the estimate A(h_k) of the next, smaller, step and returns the new row of the tableau
+     */
+    pub fn push(&mut self, estimate: f64) -> &[f64] {
+        let mut row = vec![estimate];
+
+        if let Some(previous) = self.rows.last() {
+            for j in 1..=previous.len() {
+                let order = self.leading_order + ( j - 1 ) as f64 * self.order_step;
+                let factor = self.ratio.powf(order) - 1.;
+                row.push(row[j - 1] + ( row[j - 1] - previous[j - 1] ) / factor);
+            }
+        }
+
+        self.rows.push(row);
+        self.rows.last().map_or(&[], |row| row.as_slice())
+    }
+
+    /**
+     * The most extrapolated value, the last element of the last row
+     */
+    pub fn value(&self) -> f64 {
+        self.rows.last().and_then(|row| row.last()).copied().unwrap_or(0.)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn richardson_tableau_removes_the_even_powers_of_h() {
+        // A(h) = 3 + 2 h² - 5 h⁴ + h⁶: the column j is exact up to the term h^(2j + 2)
+        let a = |h: f64| 3. + 2. * h.powi(2) - 5. * h.powi(4) + h.powi(6);
+        let mut tableau = RichardsonTableau::new(2., 2., 2.);
+
+        for k in 0..4 {
+            tableau.push(a(0.5f64.powi(k)));
+        }
+
+        assert_eq!(tableau.rows.iter().map(|row| row.len()).collect::<Vec<usize>>(), vec![1, 2, 3, 4]);
+        assert!(f64::abs(tableau.value() - 3.) < 1e-14);
+        assert!(f64::abs(tableau.rows[2][2] - 3.) > 1e-3); // the h⁶ term is left after two extrapolations
+    }
+
+    #[test]
+    fn richardson_tableau_extrapolates_a_pure_h_squared_sequence_in_one_step() {
+        let mut tableau = RichardsonTableau::new(1.4, 2., 2.);
+        let a = |h: f64| 1.5 - 4. * h * h;
+
+        assert_eq!(tableau.value(), 0.);
+
+        tableau.push(a(0.2));
+        let row = tableau.push(a(0.2 / 1.4)).to_vec();
+
+        assert!(f64::abs(row[1] - 1.5) < 1e-15);
+    }
+}
@@ -1,3 +1,6 @@
+use super::helpers::{compensated_sum, NeumaierSum, RichardsonTableau};
+
+
 /**
  * Starts the numeric integration from a rectangle that starts from the left side of the point.
  * x[i] = a + i * delta_x
@@ -5,13 +8,7 @@
 pub fn integrate_left(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
 {
     let delta_x = ( b - a ) / n as f64;
-    let mut x = a;
-    let mut sum = 0.;
-
-    for i in 0..n {
-        sum += f( x );
-        x += delta_x;
-    }
+    let sum = compensated_sum((0..n).map(|i| f( a + i as f64 * delta_x )));
 
     sum * delta_x
 }
@@ -24,13 +21,7 @@ pub fn integrate_left(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
 pub fn integrate_right(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
 {
     let delta_x = ( b - a ) / n as f64;
-    let mut x = a + delta_x;
-    let mut sum = 0.;
-
-    for _ in 0..n {
-        sum += f( x );
-        x += delta_x;
-    }
+    let sum = compensated_sum((0..n).map(|i| f( a + ( i + 1 ) as f64 * delta_x )));
 
     sum * delta_x
 }
@@ -42,13 +33,7 @@ pub fn integrate_right(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
 pub fn integrate_middle(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
 {
     let delta_x = ( b - a ) / n as f64;
-    let mut x = a + delta_x / 2.;
-    let mut sum = 0.;
-
-    for i in 0..n {
-        sum += f( x );
-        x += delta_x;
-    }
+    let sum = compensated_sum((0..n).map(|i| f( a + ( i as f64 + 0.5 ) * delta_x )));
 
     sum * delta_x
 }
@@ -56,20 +41,20 @@ pub fn integrate_middle(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
 /**
  * Integrates the function using the trapezoid method.
  * x[i] = a + i * delta_x
- * Area = [f(x) + f(x + delta_x)] * delta_x / 2
+ * Area = [f(x) + f(x + delta_x)] * delta_x / 2, summed as delta_x * ( (f(a) + f(b)) / 2 + sum( f( x[i] ), 1, n - 1 ) )
+ * so every node is evaluated once
  */
 pub fn integrate_trapezoid(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
 {
     let delta_x = ( b - a ) / n as f64;
-    let mut x = a;
-    let mut sum = 0.;
+    let mut sum = NeumaierSum::new();
+    sum.add(( f( a ) + f( b ) ) / 2.);
 
-    for _ in 0..n {
-        sum += f( x ) + f( x + delta_x );
-        x += delta_x;
+    for i in 1..n {
+        sum.add(f( a + i as f64 * delta_x ));
     }
 
-    sum * delta_x / 2.
+    sum.total() * delta_x
 }
 
 
@@ -80,17 +65,1013 @@ pub fn integrate_trapezoid(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64
  */
 pub fn integrate_simpson(f: fn(f64) -> f64, x0: f64, x2n: f64, n: u32) -> f64 {
     let h = ( x2n - x0 ) / ( 2. * n as f64 );
-    let mut sum = f( x0 ) + f( x2n );
+    let mut sum = NeumaierSum::new();
+    sum.add(f( x0 ) + f( x2n ));
 
     for i in 1..=n {
         let x_odd  = x0 + ( 2. * i as f64 - 1. ) * h;
-        sum += 4. * f( x_odd );
+        sum.add(4. * f( x_odd ));
     }
 
     for i in 1..n {
         let x_even = x0 + 2. * i as f64 * h;
-        sum += 2. * f( x_even );
+        sum.add(2. * f( x_even ));
+    }
+
+    sum.total() * h / 3.
+}
+
+
+/**
+ * Tolerances of the adaptive integrators
+ * The integration stops when error_estimate <= max( absolute_tolerance, relative_tolerance * |value| )
+ * or when max_subdivisions intervals have been bisected
+ */
+pub struct AdaptiveOptions {
+    pub absolute_tolerance: f64,
+    pub relative_tolerance: f64,
+    pub max_subdivisions: usize,
+}
+
+impl Default for AdaptiveOptions {
+    fn default() -> Self {
+        Self { absolute_tolerance: 1e-10, relative_tolerance: 1e-10, max_subdivisions: 1000 }
+    }
+}
+
+
+/**
+ * Adaptive Simpson integration
+ * Each interval compares Simpson on [a, b] (S1) with Simpson on both halves (S2):
+ * |S2 - S1| / 15 estimates the error of S2, intervals above their share of the tolerance are bisected
+ * and accepted intervals add the extrapolated S2 + (S2 - S1) / 15
+ * Function values are reused, every bisection costs 2 evaluations
+ * Returns (value, error_estimate, evaluations)
+ */
+pub fn adaptive_simpson(f: fn(f64) -> f64, a: f64, b: f64, options: &AdaptiveOptions) -> (f64, f64, usize) {
+    adaptive_simpson_dyn(&f, a, b, options)
+}
+
+
+pub(crate) fn adaptive_simpson_dyn(f: &dyn Fn(f64) -> f64, a: f64, b: f64, options: &AdaptiveOptions) -> (f64, f64, usize) {
+    let m = ( a + b ) / 2.;
+    let (fa, fm, fb) = (f( a ), f( m ), f( b ));
+    let whole = simpson_panel(a, b, fa, fm, fb);
+
+    let (fl, fr) = (f( ( a + m ) / 2. ), f( ( m + b ) / 2. ));
+    let estimate = simpson_panel(a, m, fa, fl, fm) + simpson_panel(m, b, fm, fr, fb);
+    let tolerance = f64::max(options.absolute_tolerance, options.relative_tolerance * f64::abs(estimate));
+
+    let mut evaluations = 5;
+    let mut subdivisions = 0;
+    let mut value = NeumaierSum::new();
+    let mut error = NeumaierSum::new();
+    let mut stack = vec![SimpsonInterval { a, b, fa, fm, fb, whole, tolerance }];
+
+    while let Some(interval) = stack.pop() {
+        let SimpsonInterval { a, b, fa, fm, fb, whole, tolerance } = interval;
+        let m = ( a + b ) / 2.;
+        let (fl, fr) = (f( ( a + m ) / 2. ), f( ( m + b ) / 2. ));
+        evaluations += 2;
+
+        let left = simpson_panel(a, m, fa, fl, fm);
+        let right = simpson_panel(m, b, fm, fr, fb);
+        let delta = left + right - whole;
+
+        if f64::abs(delta) <= 15. * tolerance || subdivisions >= options.max_subdivisions || m <= a || m >= b {
+            value.add(left + right + delta / 15.);
+            error.add(f64::abs(delta) / 15.);
+            continue;
+        }
+
+        subdivisions += 1;
+        stack.push(SimpsonInterval { a, b: m, fa, fm: fl, fb: fm, whole: left, tolerance: tolerance / 2. });
+        stack.push(SimpsonInterval { a: m, b, fa: fm, fm: fr, fb, whole: right, tolerance: tolerance / 2. });
+    }
+
+    (value.total(), error.total(), evaluations)
+}
+
+
+/**
+ * Adaptive Gauss-Kronrod integration with the 7 point Gauss and 15 point Kronrod pair
+ * The Kronrod value is the estimate of an interval and |K15 - G7| its error
+ * The interval with the largest error is bisected until the total error meets the tolerance
+ * Never evaluates f at the endpoints, so it handles integrable endpoint singularities
+ * Returns (value, error_estimate, evaluations)
+ */
+pub fn gauss_kronrod(f: fn(f64) -> f64, a: f64, b: f64, options: &AdaptiveOptions) -> (f64, f64, usize) {
+    gauss_kronrod_dyn(&f, a, b, options)
+}
+
+
+pub(crate) fn gauss_kronrod_dyn(f: &dyn Fn(f64) -> f64, a: f64, b: f64, options: &AdaptiveOptions) -> (f64, f64, usize) {
+    let (value, error) = gauss_kronrod_15(f, a, b);
+    let mut intervals = vec![(a, b, value, error)];
+    let mut evaluations = 15;
+
+    for _ in 0..options.max_subdivisions {
+        let value = compensated_sum(intervals.iter().map(|interval| interval.2));
+        let error = compensated_sum(intervals.iter().map(|interval| interval.3));
+
+        if error <= f64::max(options.absolute_tolerance, options.relative_tolerance * f64::abs(value)) {
+            break;
+        }
+
+        let worst = (0..intervals.len()).max_by(|i, j| intervals[*i].3.total_cmp(&intervals[*j].3)).unwrap_or(0);
+        let (a, b, worst_value, worst_error) = intervals.swap_remove(worst);
+        let m = ( a + b ) / 2.;
+
+        if m <= a || m >= b {
+            intervals.push((a, b, worst_value, worst_error)); // the interval can not be split further
+            break;
+        }
+
+        let (left, left_error) = gauss_kronrod_15(f, a, m);
+        let (right, right_error) = gauss_kronrod_15(f, m, b);
+        evaluations += 30;
+
+        intervals.push((a, m, left, left_error));
+        intervals.push((m, b, right, right_error));
+    }
+
+    let value = compensated_sum(intervals.iter().map(|interval| interval.2));
+    let error = compensated_sum(intervals.iter().map(|interval| interval.3));
+
+    (value, error, evaluations)
+}
+
+
+/**
+ * A pending interval of the adaptive Simpson method, with the values at a, (a + b) / 2 and b
+ * and its Simpson estimate
+ */
+struct SimpsonInterval {
+    a: f64,
+    b: f64,
+    fa: f64,
+    fm: f64,
+    fb: f64,
+    whole: f64,
+    tolerance: f64,
+}
+
+
+/**
+ * Simpson's rule on a single panel [a, b] from f(a), f((a + b) / 2) and f(b)
+ */
+fn simpson_panel(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> f64 {
+    ( b - a ) / 6. * ( fa + 4. * fm + fb )
+}
+
+
+/**
+ * Nodes of the 15 point Kronrod rule on [-1, 1], the nonnegative half, decreasing
+ * The odd indices are the nodes of the 7 point Gauss rule
+ */
+const KRONROD_NODES: [f64; 8] = [
+    0.9914553711208126,
+    0.9491079123427585,
+    0.8648644233597691,
+    0.7415311855993945,
+    0.5860872354676911,
+    0.4058451513773972,
+    0.20778495500789848,
+    0.,
+];
+
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022935322010529224,
+    0.06309209262997856,
+    0.10479001032225019,
+    0.14065325971552592,
+    0.1690047266392679,
+    0.19035057806478542,
+    0.20443294007529889,
+    0.20948214108472782,
+];
+
+/**
+ * Weights of the 7 point Gauss rule at KRONROD_NODES[1], [3], [5] and [7]
+ */
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.1294849661688697,
+    0.27970539148927664,
+    0.3818300505051189,
+    0.4179591836734694,
+];
+
+
+/**
+ * Returns the 15 point Kronrod estimate on [a, b] and |K15 - G7|
+ */
+fn gauss_kronrod_15(f: &dyn Fn(f64) -> f64, a: f64, b: f64) -> (f64, f64) {
+    let center = ( a + b ) / 2.;
+    let half_length = ( b - a ) / 2.;
+
+    let f_center = f( center );
+    let mut kronrod = KRONROD_WEIGHTS[7] * f_center;
+    let mut gauss = GAUSS_WEIGHTS[3] * f_center;
+
+    for (i, (node, weight)) in KRONROD_NODES.iter().zip(&KRONROD_WEIGHTS).take(7).enumerate() {
+        let dx = half_length * node;
+        let pair = f( center - dx ) + f( center + dx );
+        kronrod += weight * pair;
+
+        if i % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[i / 2] * pair;
+        }
+    }
+
+    (kronrod * half_length, f64::abs(( kronrod - gauss ) * half_length))
+}
+
+
+/**
+ * Result of the Romberg integration
+ * tableau[k][j] is the j-th extrapolation of the trapezoid rule with 2^k panels
+ * value = tableau[k][k] on the last row k, error_estimate = |tableau[k][k] - tableau[k - 1][k - 1]|
+ */
+pub struct RombergReport {
+    pub value: f64,
+    pub error_estimate: f64,
+    pub evaluations: usize,
+    pub converged: bool,
+    pub tableau: Vec<Vec<f64>>,
+}
+
+
+/**
+ * Romberg integration
+ * T(h / 2) = T(h) / 2 + (h / 2) * sum f( new midpoints ), so every halving only evaluates the new nodes
+ * Richardson extrapolation (RichardsonTableau) removes the h², h⁴, ... terms of the trapezoid error:
+ * R[k][j] = R[k][j - 1] + ( R[k][j - 1] - R[k - 1][j - 1] ) / ( 4^j - 1 )
+ * Stops when |R[k][k] - R[k - 1][k - 1]| <= tolerance (from the third row on) or after max_levels rows,
+ * max_levels is capped at ROMBERG_MAX_LEVELS
+ */
+pub fn romberg(f: fn(f64) -> f64, a: f64, b: f64, tolerance: f64, max_levels: usize) -> RombergReport {
+    let mut h = b - a;
+    let mut tableau = RichardsonTableau::new(2., 2., 2.);
+    tableau.push(h * ( f( a ) + f( b ) ) / 2.);
+
+    let mut evaluations = 2;
+    let mut error_estimate = f64::INFINITY;
+    let mut converged = false;
+
+    for k in 1..max_levels.min(ROMBERG_MAX_LEVELS) {
+        let new_nodes = 1usize << ( k - 1 );
+        let midpoint_sum = compensated_sum((0..new_nodes).map(|i| f( a + ( i as f64 + 0.5 ) * h )));
+        evaluations += new_nodes;
+        h /= 2.;
+
+        let previous_value = tableau.value();
+        let trapezoid = tableau.rows[k - 1][0] / 2. + h * midpoint_sum;
+        let row = tableau.push(trapezoid);
+
+        error_estimate = f64::abs(row[k] - previous_value);
+
+        if k >= 2 && error_estimate <= tolerance {
+            converged = true;
+            break;
+        }
+    }
+
+    RombergReport { value: tableau.value(), error_estimate, evaluations, converged, tableau: tableau.rows }
+}
+
+
+/**
+ * Bound of the Romberg rows, the last row has 2^23 panels
+ * Further rows cost over 10^7 evaluations each while rounding dominates the trapezoid sums
+ */
+const ROMBERG_MAX_LEVELS: usize = 24;
+
+
+/**
+ * Integrates f over a range with infinite limits, a = -inf and/or b = inf
+ * The range is mapped onto a finite one and integrated with gauss_kronrod,
+ * which never evaluates the (singular) endpoints of the transformed integrand
+ *  - [a, inf):    x = a + t / (1 - t),     dx = dt / (1 - t)²,           t in [0, 1)
+ *  - (-inf, b]:   x = b - (1 - t) / t,     dx = dt / t²,                 t in (0, 1]
+ *  - (-inf, inf): x = t / (1 - t²),        dx = (1 + t²) / (1 - t²)² dt, t in (-1, 1)
+ *
+ * Finite limits are integrated directly, equal limits (finite or not) give 0
+ * Returns (value, error_estimate, evaluations)
+ */
+pub fn integrate_improper(f: fn(f64) -> f64, a: f64, b: f64, options: &AdaptiveOptions) -> (f64, f64, usize) {
+    if a == b {
+        return (0., 0., 0);
     }
 
-    sum * h / 3.
+    if a > b {
+        let (value, error, evaluations) = integrate_improper(f, b, a, options);
+        return (-value, error, evaluations);
+    }
+
+    match (a.is_finite(), b.is_finite()) {
+        (true, true) => gauss_kronrod(f, a, b, options),
+        (true, false) => gauss_kronrod_dyn(&|t| f( a + t / ( 1. - t ) ) / ( ( 1. - t ) * ( 1. - t ) ), 0., 1., options),
+        (false, true) => gauss_kronrod_dyn(&|t| f( b - ( 1. - t ) / t ) / ( t * t ), 0., 1., options),
+        (false, false) => {
+            let g = |t: f64| {
+                let s = 1. - t * t;
+                f( t / s ) * ( 1. + t * t ) / ( s * s )
+            };
+            gauss_kronrod_dyn(&g, -1., 1., options)
+        }
+    }
+}
+
+
+/**
+ * Tanh-sinh (double exponential) quadrature on a finite [a, b]
+ * x = c + r tanh( pi/2 sinh(t) ), c = (a + b) / 2, r = (b - a) / 2
+ * The weights decay double exponentially towards the endpoints, so integrable endpoint
+ * singularities such as 1 / sqrt(x) on [0, 1] are handled without evaluating a or b
+ * The nodes extend up to the first integer t where the weights underflow or both abscissae
+ * round onto the endpoints (t <= 7), so nothing representable is dropped
+ * Near a nonzero endpoint the abscissae round onto it about 1e-16 |a| away, which truncates strong singularities
+ * there, e.g. x^-0.9: move them to 0 with a change of variables
+ * The step h = 2^-k is halved at every level, reusing the previous nodes
+ * Stops when the change between two levels is <= tolerance * max( 1, |value| ) or after max_levels levels
+ * Returns (value, error_estimate, evaluations), the estimate is the change between the last two levels
+ */
+pub fn tanh_sinh(f: fn(f64) -> f64, a: f64, b: f64, tolerance: f64, max_levels: usize) -> (f64, f64, usize) {
+    let center = ( a + b ) / 2.;
+    let radius = ( b - a ) / 2.;
+    let half_pi = std::f64::consts::FRAC_PI_2;
+
+    // (weight, left abscissa, right abscissa), the distance to the endpoints is computed directly to keep its precision
+    let node = |t: f64| -> (f64, f64, f64) {
+        let u = half_pi * f64::sinh(t);
+        let cosh_u = f64::cosh(u);
+        let weight = half_pi * f64::cosh(t) / ( cosh_u * cosh_u );
+        let distance = radius * 2. / ( f64::exp(2. * u) + 1. ); // r (1 - tanh(u))
+
+        (weight, a + distance, b - distance)
+    };
+
+    // sum of w(t) f(x(t)) at +-t, nodes that round onto an endpoint are not evaluated
+    let pair = |t: f64| -> (f64, usize) {
+        let (weight, left, right) = node(t);
+        let mut sum = 0.;
+        let mut count = 0;
+
+        if weight > 0. && left > a {
+            sum += f( left );
+            count += 1;
+        }
+
+        if weight > 0. && right < b {
+            sum += f( right );
+            count += 1;
+        }
+
+        (weight * sum, count)
+    };
+
+    let max_t = (1..TANH_SINH_T_LIMIT)
+        .find(|k| {
+            let (weight, left, right) = node(*k as f64);
+            weight == 0. || ( left <= a && right >= b )
+        })
+        .unwrap_or(TANH_SINH_T_LIMIT);
+
+    let mut h = 1.;
+    let mut sum = NeumaierSum::new();
+    sum.add(half_pi * f( center ));
+    let mut evaluations = 1;
+
+    for k in 1..max_t {
+        let (value, count) = pair(k as f64);
+        sum.add(value);
+        evaluations += count;
+    }
+
+    let mut value = radius * h * sum.total();
+    let mut error = f64::INFINITY;
+
+    for _ in 1..max_levels {
+        h /= 2.;
+        let new_nodes = ( max_t as f64 / ( 2. * h ) ) as usize; // the odd multiples of h below max_t
+
+        for j in 0..new_nodes {
+            let (term, count) = pair(( 2 * j + 1 ) as f64 * h);
+            sum.add(term);
+            evaluations += count;
+        }
+
+        let next = radius * h * sum.total();
+        error = f64::abs(next - value);
+        value = next;
+
+        if error <= tolerance * f64::max(1., f64::abs(value)) {
+            break;
+        }
+    }
+
+    (value, error, evaluations)
+}
+
+
+/**
+ * Cauchy principal value of the integral of f(x) / (x - c) over [a, b], a < c < b
+ * PV = integral( ( f(x) - f(c) ) / (x - c) ) + f(c) ln( (b - c) / (c - a) )
+ * The subtracted integrand is smooth, it is integrated with gauss_kronrod on [a, c] and [c, b]
+ * so that c is never evaluated
+ * Returns (value, error_estimate, evaluations)
+ */
+pub fn cauchy_principal_value(f: fn(f64) -> f64, a: f64, b: f64, c: f64, options: &AdaptiveOptions) -> (f64, f64, usize) {
+    assert!(a < c && c < b, "the singularity {} must be inside ({}, {})", c, a, b);
+
+    let fc = f( c );
+    let g = |x: f64| ( f( x ) - fc ) / ( x - c );
+
+    let (left, left_error, left_evaluations) = gauss_kronrod_dyn(&g, a, c, options);
+    let (right, right_error, right_evaluations) = gauss_kronrod_dyn(&g, c, b, options);
+
+    let value = left + right + fc * f64::ln(( b - c ) / ( c - a ));
+
+    (value, left_error + right_error, left_evaluations + right_evaluations + 1)
+}
+
+
+/**
+ * Bound of the tanh-sinh nodes, the weights underflow for t > 6.2 ( pi/2 sinh(t) > 355 )
+ */
+const TANH_SINH_T_LIMIT: usize = 8;
+
+
+/**
+ * Trapezoid rule over sampled data (x[i], y[i]), x increasing and not necessarily equally spaced
+ * sum( ( x[i + 1] - x[i] ) * ( y[i] + y[i + 1] ) / 2 )
+ */
+pub fn trapezoid_samples(x: &[f64], y: &[f64]) -> f64 {
+    assert_eq!(x.len(), y.len(), "x and y must have the same length");
+
+    compensated_sum(x.windows(2).zip(y.windows(2)).map(|(xs, ys)| ( xs[1] - xs[0] ) * ( ys[0] + ys[1] ) / 2.))
+}
+
+
+/**
+ * Simpson's rule over sampled data with non-uniform spacing
+ * Each pair of intervals integrates the parabola through its three samples
+ * With an odd number of intervals the last three integrate the cubic through their four samples,
+ * which is Simpson's 3/8 rule for equal spacing
+ * A single interval falls back to the trapezoid rule
+ */
+pub fn simpson_samples(x: &[f64], y: &[f64]) -> f64 {
+    assert_eq!(x.len(), y.len(), "x and y must have the same length");
+
+    let intervals = x.len().saturating_sub(1);
+
+    if intervals < 2 {
+        return trapezoid_samples(x, y);
+    }
+
+    let paired = if intervals.is_multiple_of(2) { intervals } else { intervals - 3 };
+    let mut sum = NeumaierSum::new();
+
+    for i in (0..paired).step_by(2) {
+        sum.add(interpolant_integral(&x[i..i + 3], &y[i..i + 3], x[i], x[i + 2]));
+    }
+
+    if paired < intervals {
+        sum.add(interpolant_integral(&x[paired..], &y[paired..], x[paired], x[intervals]));
+    }
+
+    sum.total()
+}
+
+
+/**
+ * Running trapezoid integral: result[i] is the integral from x[0] to x[i], result[0] = 0
+ */
+pub fn cumulative_trapezoid(x: &[f64], y: &[f64]) -> Vec<f64> {
+    assert_eq!(x.len(), y.len(), "x and y must have the same length");
+
+    let mut total = NeumaierSum::new();
+    let mut result = Vec::with_capacity(x.len());
+
+    if !x.is_empty() {
+        result.push(0.);
+    }
+
+    for (xs, ys) in x.windows(2).zip(y.windows(2)) {
+        total.add(( xs[1] - xs[0] ) * ( ys[0] + ys[1] ) / 2.);
+        result.push(total.total());
+    }
+
+    result
+}
+
+
+/**
+ * Running Simpson integral: result[i] is the integral from x[0] to x[i], result[0] = 0
+ * Each interval integrates the parabola through its samples and the next one (the previous one on the last interval),
+ * so every partial integral has the third order accuracy of Simpson's rule
+ */
+pub fn cumulative_simpson(x: &[f64], y: &[f64]) -> Vec<f64> {
+    assert_eq!(x.len(), y.len(), "x and y must have the same length");
+
+    let n = x.len();
+
+    if n < 3 {
+        return cumulative_trapezoid(x, y);
+    }
+
+    let mut total = NeumaierSum::new();
+    let mut result = vec![0.];
+
+    for i in 1..n {
+        let start = usize::min(i - 1, n - 3);
+        total.add(interpolant_integral(&x[start..start + 3], &y[start..start + 3], x[i - 1], x[i]));
+        result.push(total.total());
+    }
+
+    result
+}
+
+
+/**
+ * Integral over [lo, hi] of the polynomial interpolating (xs[i], ys[i]), at most 4 samples
+ * The 2 point Gauss-Legendre rule is exact for the interpolant, a cubic at most
+ */
+fn interpolant_integral(xs: &[f64], ys: &[f64], lo: f64, hi: f64) -> f64 {
+    let center = ( lo + hi ) / 2.;
+    let half_length = ( hi - lo ) / 2.;
+    let offset = half_length / f64::sqrt(3.);
+
+    let lagrange = |t: f64| -> f64 {
+        (0..xs.len())
+            .map(|j| {
+                let basis: f64 = (0..xs.len()).filter(|k| *k != j).map(|k| ( t - xs[k] ) / ( xs[j] - xs[k] )).product();
+                ys[j] * basis
+            })
+            .sum()
+    };
+
+    half_length * ( lagrange(center - offset) + lagrange(center + offset) )
+}
+
+
+/**
+ * Composite Simpson's 3/8 rule with n panels of three intervals, h = (b - a) / 3n
+ * (3h / 8) * ( f( x0 ) + 3 f( x1 ) + 3 f( x2 ) + f( x3 ) ) on every panel
+ * Error O(h⁴), as Simpson's 1/3 rule, with a smaller error constant for panels of the same width
+ */
+pub fn integrate_simpson_three_eighths(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64 {
+    integrate_newton_cotes(f, a, b, n, 4, true)
+}
+
+
+/**
+ * Composite Boole's rule with n panels of four intervals, h = (b - a) / 4n
+ * (2h / 45) * ( 7 f( x0 ) + 32 f( x1 ) + 12 f( x2 ) + 32 f( x3 ) + 7 f( x4 ) ) on every panel
+ * Error O(h⁶)
+ */
+pub fn integrate_boole(f: fn(f64) -> f64, a: f64, b: f64, n: u32) -> f64 {
+    integrate_newton_cotes(f, a, b, n, 5, true)
+}
+
+
+/**
+ * Composite Newton-Cotes rule with n panels and the given number of points per panel
+ * closed: the points are the panel endpoints and the points between them, points - 1 intervals
+ * open: the points are strictly inside the panel, points + 1 intervals
+ * Closed rules with 9 or more points, and open rules with 3 or more, have negative weights
+ * and amplify rounding errors, prefer more panels to more points
+ * points is limited as in newton_cotes_weights
+ */
+pub fn integrate_newton_cotes(f: fn(f64) -> f64, a: f64, b: f64, n: u32, points: usize, closed: bool) -> f64 {
+    let weights = newton_cotes_weights(points, closed);
+    let intervals = if closed { points - 1 } else { points + 1 };
+    let first_node = if closed { 0 } else { 1 };
+
+    let h = ( b - a ) / ( n as usize * intervals ) as f64;
+    let mut sum = NeumaierSum::new();
+
+    for panel in 0..n as usize {
+        for (i, weight) in weights.iter().enumerate() {
+            sum.add(weight * f( a + ( panel * intervals + first_node + i ) as f64 * h ));
+        }
+    }
+
+    sum.total() * h
+}
+
+
+const MAX_CLOSED_NEWTON_COTES_POINTS: usize = 22;
+const MAX_OPEN_NEWTON_COTES_POINTS: usize = 21;
+
+
+/**
+ * Weights of the Newton-Cotes rule with the given number of points, in units of the step h
+ * w_j = integral( L_j(t) dt ) over the panel, L_j the Lagrange basis of the nodes t = 0, 1, 2, ...
+ * The integrals are computed exactly in rational arithmetic, then rounded once to f64
+ *  - closed, nodes 0..points - 1 over [0, points - 1]: 2 points gives the trapezoid (1/2, 1/2),
+ *    3 points Simpson (1/3, 4/3, 1/3)
+ *  - open, nodes 1..points over [0, points + 1]: 1 point gives the midpoint rule (2)
+ *
+ * The exact i128 arithmetic is limited to 22 points for the closed rules and 21 for the open ones
+ */
+pub fn newton_cotes_weights(points: usize, closed: bool) -> Vec<f64> {
+    assert!(points >= if closed { 2 } else { 1 }, "not enough points for a Newton-Cotes rule");
+    assert!(
+        points <= if closed { MAX_CLOSED_NEWTON_COTES_POINTS } else { MAX_OPEN_NEWTON_COTES_POINTS },
+        "the exact Newton-Cotes weights overflow beyond {} points for closed rules and {} for open rules",
+        MAX_CLOSED_NEWTON_COTES_POINTS,
+        MAX_OPEN_NEWTON_COTES_POINTS
+    );
+
+    let nodes: Vec<i128> = if closed { (0..points as i128).collect() } else { (1..=points as i128).collect() };
+    let length = if closed { points as i128 - 1 } else { points as i128 + 1 };
+
+    (0..points)
+        .map(|j| {
+            // numerator of L_j: product of (t - t_k), integer coefficients in increasing powers
+            let mut coefficients: Vec<i128> = vec![1];
+            let mut denominator: i128 = 1;
+
+            for (k, node) in nodes.iter().enumerate().filter(|(k, _)| *k != j) {
+                let mut next = vec![0; coefficients.len() + 1];
+
+                for (p, c) in coefficients.iter().enumerate() {
+                    next[p + 1] += c;
+                    next[p] -= c * node;
+                }
+
+                coefficients = next;
+                denominator *= nodes[j] - nodes[k];
+            }
+
+            // integral over [0, length] of sum( c_p t^p ) = sum( c_p length^(p + 1) / (p + 1) )
+            let mut integral = Ratio::new(0, 1);
+            let mut length_power = length;
+
+            for (p, c) in coefficients.iter().enumerate() {
+                integral = integral.add(&Ratio::new(c * length_power, p as i128 + 1));
+                length_power *= length;
+            }
+
+            integral.num as f64 / ( integral.den * denominator ) as f64
+        })
+        .collect()
+}
+
+
+/**
+ * Exact fraction num / den, den > 0, in lowest terms
+ */
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    fn new(num: i128, den: i128) -> Self {
+        let divisor = gcd(num, den).max(1) * den.signum();
+        Self { num: num / divisor, den: den / divisor }
+    }
+
+    fn add(&self, other: &Ratio) -> Ratio {
+        let common = self.den / gcd(self.den, other.den) * other.den;
+        Ratio::new(self.num * ( common / self.den ) + other.num * ( common / other.den ), common)
+    }
+}
+
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const N: u32 = 10_000_000;
+
+    #[test]
+    fn rectangle_and_trapezoid_rules_are_accurate_with_ten_million_panels() {
+        // the discretization errors are ~1.6e-14 (left, right, trapezoid) and ~8.2e-15 (middle)
+        assert!(f64::abs(integrate_left(f64::sin, 0., PI, N) - 2.) < 5e-14);
+        assert!(f64::abs(integrate_right(f64::sin, 0., PI, N) - 2.) < 5e-14);
+        assert!(f64::abs(integrate_middle(f64::sin, 0., PI, N) - 2.) < 5e-14);
+        assert!(f64::abs(integrate_trapezoid(f64::sin, 0., PI, N) - 2.) < 5e-14);
+    }
+
+    #[test]
+    fn simpson_rule_is_accurate_with_ten_million_panels() {
+        assert!(f64::abs(integrate_simpson(f64::sin, 0., PI, N) - 2.) < 1e-14);
+    }
+
+    #[test]
+    fn compensated_summation_beats_naive_summation() {
+        // the former midpoint rule: drifting nodes x += h and naive accumulation
+        let h = PI / N as f64;
+        let mut x = h / 2.;
+        let mut naive = 0.;
+
+        for _ in 0..N {
+            naive += f64::sin(x);
+            x += h;
+        }
+
+        let naive_error = f64::abs(naive * h - 2.);
+        let compensated_error = f64::abs(integrate_middle(f64::sin, 0., PI, N) - 2.);
+
+        assert!(compensated_error < naive_error / 100.);
+
+        // same nodes, only the summation differs
+        let terms = (0..N).map(|i| f64::sin(( i as f64 + 0.5 ) * h));
+        let naive_sum: f64 = terms.clone().sum();
+        let exact_sum = 2. / h + h / 12.; // midpoint rule: h * sum = 2 + h² / 12 + O(h⁴)
+
+        assert!(f64::abs(compensated_sum(terms) - exact_sum) < 1e-8);
+        assert!(f64::abs(naive_sum - exact_sum) > 1e-8);
+    }
+
+    #[test]
+    fn newton_cotes_weights_integrate_monomials_exactly() {
+        for closed in [true, false] {
+            let max_points = if closed { MAX_CLOSED_NEWTON_COTES_POINTS } else { MAX_OPEN_NEWTON_COTES_POINTS };
+
+            for points in (if closed { 2 } else { 1 })..=max_points {
+                let weights = newton_cotes_weights(points, closed);
+                let first_node = if closed { 0. } else { 1. };
+                let length = if closed { points as f64 - 1. } else { points as f64 + 1. };
+
+                // the rules with an odd number of points gain one degree by symmetry
+                let degree = if points % 2 == 1 { points } else { points - 1 };
+                let tolerance = if points <= 10 { 1e-14 } else { 1e-9 };
+
+                // k = 0: the weights sum to the panel length
+                for k in 0..=degree {
+                    let rule: f64 = weights.iter().enumerate().map(|(j, w)| w * ( first_node + j as f64 ).powi(k as i32)).sum();
+                    let exact = length.powi(k as i32 + 1) / ( k as f64 + 1. );
+
+                    assert!(f64::abs(rule - exact) <= tolerance * exact, "closed = {closed}, points = {points}, k = {k}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn newton_cotes_rules_integrate_polynomials_on_an_interval() {
+        let exact = ( 3f64.powi(6) - 1. ) / 6.;
+
+        assert!(f64::abs(integrate_boole(|x| x.powi(5), 1., 3., 3) - exact) < 1e-12);
+        assert!(f64::abs(integrate_newton_cotes(|x| x.powi(5), 1., 3., 2, 7, true) - exact) < 1e-12);
+        assert!(f64::abs(integrate_newton_cotes(|x| x.powi(5), 1., 3., 2, 5, false) - exact) < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow")]
+    fn newton_cotes_weights_reject_too_many_points() {
+        newton_cotes_weights(MAX_OPEN_NEWTON_COTES_POINTS + 1, false);
+    }
+
+    #[test]
+    fn adaptive_integrators_match_a_smooth_closed_form() {
+        let options = AdaptiveOptions::default();
+        let exact = std::f64::consts::E - 1.;
+
+        let (value, error, _) = adaptive_simpson(f64::exp, 0., 1., &options);
+        assert!(f64::abs(value - exact) < 1e-10 && error <= 1e-10);
+
+        let (value, error, evaluations) = gauss_kronrod(f64::exp, 0., 1., &options);
+        assert!(f64::abs(value - exact) < 1e-14 && error <= 1e-10);
+        assert_eq!(evaluations, 15);
+    }
+
+    #[test]
+    fn adaptive_integrators_meet_the_tolerance_on_a_peaked_integrand() {
+        // integral of 1 / ( x² + 1e-4 ) over [-1, 1] is 200 atan(100), the peak at 0 is 10⁴ high and 0.01 wide
+        let peak = |x: f64| 1. / ( x * x + 1e-4 );
+        let exact = 200. * f64::atan(100.);
+
+        // the halved tolerances of adaptive_simpson need ~9000 bisections at 1e-10
+        for tolerance in [1e-6, 1e-10] {
+            let options = AdaptiveOptions { absolute_tolerance: tolerance, relative_tolerance: 0., max_subdivisions: 100_000 };
+
+            let (value, error, _) = adaptive_simpson(peak, -1., 1., &options);
+            assert!(error <= tolerance, "adaptive_simpson: error estimate {error} above {tolerance}");
+            assert!(f64::abs(value - exact) <= tolerance, "adaptive_simpson: error {} above {tolerance}", f64::abs(value - exact));
+
+            let (value, error, _) = gauss_kronrod(peak, -1., 1., &options);
+            assert!(error <= tolerance, "gauss_kronrod: error estimate {error} above {tolerance}");
+            assert!(f64::abs(value - exact) <= error, "gauss_kronrod: error {} above its estimate", f64::abs(value - exact));
+        }
+    }
+
+    #[test]
+    fn adaptive_integrators_stop_at_max_subdivisions() {
+        let peak = |x: f64| 1. / ( x * x + 1e-4 );
+        let options = AdaptiveOptions { max_subdivisions: 3, ..AdaptiveOptions::default() };
+
+        // not converged: the error estimate stays above the tolerance
+        let (_, error, evaluations) = adaptive_simpson(peak, -1., 1., &options);
+        assert!(error > 1e-10);
+        assert_eq!(evaluations, 5 + 2 * ( 2 * 3 + 1 ));
+
+        let (_, error, evaluations) = gauss_kronrod(peak, -1., 1., &options);
+        assert!(error > 1e-10);
+        assert_eq!(evaluations, 15 + 30 * 3);
+    }
+
+    #[test]
+    fn romberg_diagonal_converges_to_the_integral() {
+        let report = romberg(f64::sin, 0., PI, -1., 8);
+        let diagonal: Vec<f64> = report.tableau.iter().enumerate().map(|(k, row)| f64::abs(row[k] - 2.)).collect();
+
+        assert!(!report.converged);
+        assert_eq!(report.tableau.len(), 8);
+        assert_eq!(report.evaluations, 129);
+
+        // R[k][k] is exact to O(h^(2k + 2)), each row gains more digits than the last until rounding
+        for k in 1..6 {
+            assert!(diagonal[k] < diagonal[k - 1] / 20.);
+        }
+
+        assert!(diagonal[7] < 1e-14 && f64::abs(report.value - 2.) < 1e-14);
+    }
+
+    #[test]
+    fn romberg_stops_once_the_tolerance_is_met() {
+        let report = romberg(f64::sin, 0., PI, 1e-8, 30);
+        let levels = report.tableau.len();
+
+        assert!(report.converged && report.error_estimate <= 1e-8);
+        assert!(levels < 8);
+        assert_eq!(report.evaluations, ( 1 << ( levels - 1 ) ) + 1);
+        assert!(f64::abs(report.value - 2.) < 1e-10);
+    }
+
+    #[test]
+    fn romberg_caps_max_levels() {
+        // a negative tolerance is never met, the rows stop at ROMBERG_MAX_LEVELS instead of overflowing 1 << ( k - 1 )
+        let report = romberg(|x| x, 0., 1., -1., 100);
+
+        assert!(!report.converged);
+        assert_eq!(report.tableau.len(), ROMBERG_MAX_LEVELS);
+    }
+
+    /**
+     * x = 0, 0.1, 0.35, 0.45, 0.8, ... irregularly spaced samples on [0, count - 1] / 2
+     */
+    fn non_uniform_samples(count: usize) -> Vec<f64> {
+        (0..count).map(|i| ( i as f64 + if i == 0 || i == count - 1 { 0. } else { 0.3 * f64::sin(3. * i as f64) } ) / 2.).collect()
+    }
+
+    #[test]
+    fn trapezoid_samples_is_exact_for_lines_and_second_order() {
+        let x = non_uniform_samples(9);
+        let y: Vec<f64> = x.iter().map(|x| 3. * x - 1.).collect();
+        let b = x[8];
+
+        assert!(f64::abs(trapezoid_samples(&x, &y) - ( 1.5 * b * b - b )) < 1e-14);
+
+        let error = |n: usize| {
+            let x: Vec<f64> = (0..=n).map(|i| PI * i as f64 / n as f64).collect();
+            let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+            f64::abs(trapezoid_samples(&x, &y) - 2.)
+        };
+        let ratio = error(50) / error(100);
+
+        assert!(f64::abs(ratio - 4.) < 0.01);
+        assert_eq!(trapezoid_samples(&[1.], &[2.]), 0.);
+        assert_eq!(trapezoid_samples(&[], &[]), 0.);
+    }
+
+    #[test]
+    fn simpson_samples_handles_odd_and_even_counts() {
+        let quadratic = |x: f64| 2. * x * x - x + 0.5;
+        let cubic = |x: f64| x * x * x - 2. * x;
+
+        for count in 3..=10 {
+            // non-uniform: each parabola (and the last cubic) is exact for quadratics
+            let x = non_uniform_samples(count);
+            let y: Vec<f64> = x.iter().map(|x| quadratic(*x)).collect();
+            let b = x[count - 1];
+
+            assert!(f64::abs(simpson_samples(&x, &y) - ( 2. * b.powi(3) / 3. - b * b / 2. + b / 2. )) < 1e-13, "count = {count}");
+
+            // uniform: Simpson's 1/3 and 3/8 rules are exact for cubics
+            let x: Vec<f64> = (0..count).map(|i| i as f64 / 4.).collect();
+            let y: Vec<f64> = x.iter().map(|x| cubic(*x)).collect();
+            let b = x[count - 1];
+
+            assert!(f64::abs(simpson_samples(&x, &y) - ( b.powi(4) / 4. - b * b )) < 1e-13, "count = {count}");
+        }
+
+        let x: Vec<f64> = (0..=100).map(|i| PI * i as f64 / 100.).collect();
+        let y: Vec<f64> = x.iter().map(|x| x.sin()).collect();
+
+        assert!(f64::abs(simpson_samples(&x, &y) - 2.) < 1e-7);
+        assert!(f64::abs(simpson_samples(&x[..100], &y[..100]) - ( 1. - f64::cos(x[99]) )) < 1e-7);
+    }
+
+    #[test]
+    fn cumulative_integrals_end_at_the_total() {
+        for count in [2, 3, 8, 9] {
+            let x: Vec<f64> = non_uniform_samples(count).iter().map(|x| x / 10.).collect();
+            let y: Vec<f64> = x.iter().map(|x| f64::exp(-x)).collect();
+
+            let trapezoid = cumulative_trapezoid(&x, &y);
+            assert_eq!(trapezoid.len(), count);
+            assert_eq!(trapezoid[0], 0.);
+            assert!(f64::abs(trapezoid[count - 1] - trapezoid_samples(&x, &y)) < 1e-15);
+
+            let simpson = cumulative_simpson(&x, &y);
+            assert_eq!(simpson.len(), count);
+
+            if count == 2 {
+                assert_eq!(simpson, trapezoid);
+                continue;
+            }
+
+            // the parabolas differ from those of simpson_samples, both are closer to the integral than the trapezoid rule
+            let exact = 1. - f64::exp(-x[count - 1]);
+            let simpson_error = f64::abs(simpson[count - 1] - exact);
+
+            assert!(simpson_error < 1e-5 && f64::abs(simpson_samples(&x, &y) - exact) < 1e-5);
+            assert!(simpson_error < f64::abs(trapezoid[count - 1] - exact) / 10.);
+        }
+
+        assert!(cumulative_trapezoid(&[], &[]).is_empty());
+        assert!(cumulative_simpson(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn cumulative_simpson_is_exact_for_quadratics_at_every_sample() {
+        for count in [3, 4, 7, 10] {
+            let x = non_uniform_samples(count);
+            let y: Vec<f64> = x.iter().map(|x| 3. * x * x + 1.).collect();
+            let running = cumulative_simpson(&x, &y);
+
+            for (xi, value) in x.iter().zip(&running) {
+                assert!(f64::abs(value - ( xi.powi(3) + xi )) < 1e-13);
+            }
+
+            assert!(f64::abs(running[count - 1] - simpson_samples(&x, &y)) < 1e-13);
+        }
+    }
+
+    #[test]
+    fn integrate_improper_matches_closed_forms_on_infinite_ranges() {
+        let options = AdaptiveOptions::default();
+
+        let (value, error, _) = integrate_improper(|x| f64::exp(-x * x), 0., f64::INFINITY, &options);
+        assert!(f64::abs(value - PI.sqrt() / 2.) < 1e-10 && error < 1e-9);
+
+        let (value, _, _) = integrate_improper(f64::exp, f64::NEG_INFINITY, 0., &options);
+        assert!(f64::abs(value - 1.) < 1e-10);
+
+        let (value, _, _) = integrate_improper(|x| 1. / ( 1. + x * x ), f64::NEG_INFINITY, f64::INFINITY, &options);
+        assert!(f64::abs(value - PI) < 1e-10);
+
+        let (value, _, _) = integrate_improper(|x| 1. / ( 1. + x * x ), f64::INFINITY, 0., &options);
+        assert!(f64::abs(value + PI / 2.) < 1e-10);
+    }
+
+    #[test]
+    fn integrate_improper_over_an_empty_infinite_range_is_zero() {
+        let options = AdaptiveOptions::default();
+
+        assert_eq!(integrate_improper(|_| 1., f64::INFINITY, f64::INFINITY, &options), (0., 0., 0));
+        assert_eq!(integrate_improper(|_| 1., f64::NEG_INFINITY, f64::NEG_INFINITY, &options), (0., 0., 0));
+    }
+
+    #[test]
+    fn tanh_sinh_handles_endpoint_singularities() {
+        let (value, error, _) = tanh_sinh(|x| 1. / x.sqrt(), 0., 1., 1e-12, 12);
+        assert!(f64::abs(value - 2.) < 1e-12 && error < 1e-10);
+
+        let (value, _, _) = tanh_sinh(|x| x.powf(-0.9), 0., 1., 1e-12, 12);
+        assert!(f64::abs(value - 10.) < 1e-8);
+
+        let (value, _, _) = tanh_sinh(|x| f64::ln(x) * f64::ln(1. - x), 0., 1., 1e-12, 12);
+        assert!(f64::abs(value - ( 2. - PI * PI / 6. )) < 1e-12);
+    }
+
+    #[test]
+    fn cauchy_principal_value_matches_closed_forms() {
+        let options = AdaptiveOptions::default();
+
+        // PV of 1 / x over [-1, 2] is ln 2
+        let (value, _, _) = cauchy_principal_value(|_| 1., -1., 2., 0., &options);
+        assert!(f64::abs(value - f64::ln(2.)) < 1e-14);
+
+        // PV of e^x / x over [-1, 1] is 2 Shi(1), the hyperbolic sine integral
+        let (value, error, _) = cauchy_principal_value(f64::exp, -1., 1., 0., &options);
+        assert!(f64::abs(value - 2. * 1.0572508753757285) < 1e-12 && error < 1e-9);
+    }
 }
@@ -1,6 +1,8 @@
 pub mod function_roots;
+pub mod gaussian_quadrature;
 pub mod helpers;
 pub mod integration;
+pub mod multiple_integration;
 pub mod differentiation;
 
 use function_roots::{
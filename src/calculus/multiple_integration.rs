@@ -0,0 +1,577 @@
+use std::cell::Cell;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::types::ScalarFunction;
+use super::gaussian_quadrature::QuadratureRule;
+use super::integration::{gauss_kronrod_dyn, AdaptiveOptions};
+
+
+/**
+ * Iterated integral over the region a <= x <= b, y_lower(x) <= y <= y_upper(x)
+ * Rectangles use constant limits, e.g. |_| 0. and |_| 1.
+ * The inner and the outer integrals are adaptive Gauss-Kronrod with the same options,
+ * the error estimate is the one of the outer integration
+ * Returns (value, error_estimate, evaluations)
+ */
+pub fn iterated_integral_2d(
+    f: fn(f64, f64) -> f64,
+    a: f64,
+    b: f64,
+    y_lower: fn(f64) -> f64,
+    y_upper: fn(f64) -> f64,
+    options: &AdaptiveOptions,
+) -> (f64, f64, usize) {
+    let evaluations = Cell::new(0);
+
+    let inner = |x: f64| {
+        let (value, _, count) = gauss_kronrod_dyn(&|y| f( x, y ), y_lower( x ), y_upper( x ), options);
+        evaluations.set(evaluations.get() + count);
+        value
+    };
+
+    let (value, error, _) = gauss_kronrod_dyn(&inner, a, b, options);
+
+    (value, error, evaluations.get())
+}
+
+
+/**
+ * Iterated integral over the region a <= x <= b, y_lower(x) <= y <= y_upper(x),
+ * z_lower(x, y) <= z <= z_upper(x, y)
+ * Returns (value, error_estimate, evaluations), as iterated_integral_2d
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn iterated_integral_3d(
+    f: fn(f64, f64, f64) -> f64,
+    a: f64,
+    b: f64,
+    y_lower: fn(f64) -> f64,
+    y_upper: fn(f64) -> f64,
+    z_lower: fn(f64, f64) -> f64,
+    z_upper: fn(f64, f64) -> f64,
+    options: &AdaptiveOptions,
+) -> (f64, f64, usize) {
+    let evaluations = Cell::new(0);
+
+    let inner = |x: f64| {
+        let middle = |y: f64| {
+            let (value, _, count) = gauss_kronrod_dyn(&|z| f( x, y, z ), z_lower( x, y ), z_upper( x, y ), options);
+            evaluations.set(evaluations.get() + count);
+            value
+        };
+
+        gauss_kronrod_dyn(&middle, y_lower( x ), y_upper( x ), options).0
+    };
+
+    let (value, error, _) = gauss_kronrod_dyn(&inner, a, b, options);
+
+    (value, error, evaluations.get())
+}
+
+
+/**
+ * Smolyak sparse grid cubature over the box bounds[i].0 <= x[i] <= bounds[i].1
+ * Combination technique with 1-D Gauss-Legendre rules of 2l - 1 points at level l:
+ * A(q, d) = sum over q - d + 1 <= |l| <= q of (-1)^(q - |l|) C(d - 1, q - |l|) U(l_1) x ... x U(l_d),
+ * q = level + d - 1
+ * Exact for polynomials of total degree up to 2 * level - 1, with far fewer points than the full tensor grid
+ * Returns (value, evaluations)
+ */
+pub fn sparse_grid(f: &ScalarFunction, bounds: &[(f64, f64)], level: usize) -> (f64, usize) {
+    assert!(level >= 1, "the sparse grid level starts at 1");
+
+    let d = bounds.len();
+    let q = level + d - 1;
+    let rules: Vec<QuadratureRule> = (1..=level).map(|l| QuadratureRule::gauss_legendre(2 * l - 1)).collect();
+
+    let mut value = 0.;
+    let mut evaluations = 0;
+    let mut index = vec![1; d];
+
+    loop {
+        let norm: usize = index.iter().sum();
+
+        if norm + d > q {
+            let k = q - norm;
+            let coefficient = if k.is_multiple_of(2) { 1. } else { -1. } * binomial(d - 1, k);
+            let tensor_rules: Vec<&QuadratureRule> = index.iter().map(|l| &rules[l - 1]).collect();
+            let (tensor_value, count) = tensor_product(f, bounds, &tensor_rules);
+
+            value += coefficient * tensor_value;
+            evaluations += count;
+        }
+
+        if !next_multi_index(&mut index, q) {
+            break;
+        }
+    }
+
+    (value, evaluations)
+}
+
+
+/**
+ * Plain Monte Carlo integration over a box with uniformly distributed samples
+ * value = V * mean( f ), standard_error = V * sqrt( var( f ) / samples ), V the volume of the box
+ * Returns (value, standard_error)
+ */
+pub fn monte_carlo(f: &ScalarFunction, bounds: &[(f64, f64)], samples: usize, seed: u64) -> (f64, f64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut x = vec![0.; bounds.len()];
+    let mut statistics = RunningStatistics::default();
+
+    for _ in 0..samples {
+        for (xi, (lower, upper)) in x.iter_mut().zip(bounds) {
+            *xi = lower + ( upper - lower ) * rng.gen::<f64>();
+        }
+
+        statistics.push(f( &x ));
+    }
+
+    let volume = box_volume(bounds);
+
+    (volume * statistics.mean, volume * f64::sqrt(statistics.variance() / samples as f64))
+}
+
+
+/**
+ * Stratified Monte Carlo integration
+ * Every side of the box is split into strata_per_dimension equal parts and each of the
+ * strata_per_dimension^d cells receives samples_per_stratum uniform samples
+ * value = sum( V_j mean_j ), standard_error = sqrt( sum( V_j² var_j / n_j ) )
+ * samples_per_stratum must be at least 2 for the variance estimate
+ * Returns (value, standard_error)
+ */
+pub fn stratified_monte_carlo(f: &ScalarFunction, bounds: &[(f64, f64)], strata_per_dimension: usize, samples_per_stratum: usize, seed: u64) -> (f64, f64) {
+    assert!(samples_per_stratum >= 2, "at least two samples per stratum are needed to estimate the variance");
+
+    let d = bounds.len();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let widths: Vec<f64> = bounds.iter().map(|(lower, upper)| ( upper - lower ) / strata_per_dimension as f64).collect();
+    let cell_volume: f64 = widths.iter().product();
+
+    let mut cell = vec![0; d];
+    let mut x = vec![0.; d];
+    let mut value = 0.;
+    let mut variance = 0.;
+
+    loop {
+        let mut statistics = RunningStatistics::default();
+
+        for _ in 0..samples_per_stratum {
+            for (i, xi) in x.iter_mut().enumerate() {
+                *xi = bounds[i].0 + ( cell[i] as f64 + rng.gen::<f64>() ) * widths[i];
+            }
+
+            statistics.push(f( &x ));
+        }
+
+        value += cell_volume * statistics.mean;
+        variance += cell_volume * cell_volume * statistics.variance() / samples_per_stratum as f64;
+
+        // next cell, odometer order
+        let Some(i) = cell.iter().position(|c| c + 1 < strata_per_dimension) else {
+            break;
+        };
+
+        cell[i] += 1;
+        cell[..i].iter_mut().for_each(|c| *c = 0);
+    }
+
+    (value, f64::sqrt(variance))
+}
+
+
+/**
+ * Low discrepancy sequences for the quasi-Monte Carlo integration
+ *  - Halton: radical inverses in the first d prime bases
+ *  - Sobol: base 2 digital sequence with the Joe-Kuo direction numbers, up to 13 dimensions
+ *
+ */
+#[derive(Clone, Copy, Debug)]
+pub enum LowDiscrepancySequence {
+    Halton,
+    Sobol,
+}
+
+
+/**
+ * Randomized quasi-Monte Carlo integration over a box
+ * The samples are split into QMC_REPLICATES copies of the sequence, each shifted modulo 1 by an
+ * independent uniform random vector (Cranley-Patterson rotation)
+ * The replicates are independent unbiased estimates, their spread gives the standard error
+ * The error decays close to O( log(N)^d / N ) for smooth integrands, against O( 1 / sqrt(N) ) for Monte Carlo
+ * Returns (value, standard_error)
+ */
+pub fn quasi_monte_carlo(f: &ScalarFunction, bounds: &[(f64, f64)], samples: usize, sequence: LowDiscrepancySequence, seed: u64) -> (f64, f64) {
+    let d = bounds.len();
+    let points_per_replicate = usize::max(1, samples / QMC_REPLICATES);
+    let points: Vec<Vec<f64>> = match sequence {
+        LowDiscrepancySequence::Halton => halton_points(d, points_per_replicate),
+        LowDiscrepancySequence::Sobol => sobol_points(d, points_per_replicate),
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut x = vec![0.; d];
+    let mut estimates = RunningStatistics::default();
+    let volume = box_volume(bounds);
+
+    for _ in 0..QMC_REPLICATES {
+        let shift: Vec<f64> = (0..d).map(|_| rng.gen::<f64>()).collect();
+        let mut sum = 0.;
+
+        for point in &points {
+            for (i, xi) in x.iter_mut().enumerate() {
+                let u = ( point[i] + shift[i] ).fract();
+                *xi = bounds[i].0 + ( bounds[i].1 - bounds[i].0 ) * u;
+            }
+
+            sum += f( &x );
+        }
+
+        estimates.push(volume * sum / points.len() as f64);
+    }
+
+    (estimates.mean, f64::sqrt(estimates.variance() / QMC_REPLICATES as f64))
+}
+
+
+/**
+ * First n points of the Halton sequence in d dimensions, starting at index 1
+ */
+pub fn halton_points(d: usize, n: usize) -> Vec<Vec<f64>> {
+    let bases = first_primes(d);
+
+    (1..=n)
+        .map(|index| bases.iter().map(|base| radical_inverse(index, *base)).collect())
+        .collect()
+}
+
+
+/**
+ * First n points of the Sobol sequence in d <= 13 dimensions, in Gray code order, starting at the origin
+ */
+pub fn sobol_points(d: usize, n: usize) -> Vec<Vec<f64>> {
+    assert!(d <= SOBOL_PARAMETERS.len() + 1, "the Sobol sequence is available up to {} dimensions", SOBOL_PARAMETERS.len() + 1);
+
+    let directions: Vec<[u32; SOBOL_BITS]> = (0..d).map(sobol_directions).collect();
+    let mut state = vec![0u32; d];
+    let mut points = Vec::with_capacity(n);
+    let scale = 1. / ( 1u64 << SOBOL_BITS ) as f64;
+
+    for index in 0..n {
+        points.push(state.iter().map(|x| *x as f64 * scale).collect());
+
+        // the next point flips the direction number of the lowest zero bit of index
+        let bit = ( !index ).trailing_zeros() as usize;
+
+        if bit >= SOBOL_BITS {
+            break;
+        }
+
+        for (x, v) in state.iter_mut().zip(&directions) {
+            *x ^= v[bit];
+        }
+    }
+
+    points
+}
+
+
+const QMC_REPLICATES: usize = 16;
+const SOBOL_BITS: usize = 32;
+
+/**
+ * (degree s, coefficients a, initial direction numbers m) of the primitive polynomials
+ * of the dimensions 2 to 13 (Joe, Kuo), the first dimension is the van der Corput sequence
+ */
+const SOBOL_PARAMETERS: [(usize, u32, [u32; 5]); 12] = [
+    (1, 0, [1, 0, 0, 0, 0]),
+    (2, 1, [1, 3, 0, 0, 0]),
+    (3, 1, [1, 3, 1, 0, 0]),
+    (3, 2, [1, 1, 1, 0, 0]),
+    (4, 1, [1, 1, 3, 3, 0]),
+    (4, 4, [1, 3, 5, 13, 0]),
+    (5, 2, [1, 1, 5, 5, 17]),
+    (5, 4, [1, 1, 5, 5, 5]),
+    (5, 7, [1, 1, 7, 11, 19]),
+    (5, 11, [1, 1, 5, 1, 1]),
+    (5, 13, [1, 1, 1, 3, 11]),
+    (5, 14, [1, 3, 5, 5, 31]),
+];
+
+
+/**
+ * Direction numbers v[k] = m[k] 2^(31 - k) of a Sobol dimension, extended with the recurrence
+ * v[k] = v[k - s] ^ ( v[k - s] >> s ) ^ sum( a_j v[k - j] ), j = 1..s - 1
+ */
+fn sobol_directions(dimension: usize) -> [u32; SOBOL_BITS] {
+    let mut v = [0u32; SOBOL_BITS];
+
+    if dimension == 0 {
+        for (k, vk) in v.iter_mut().enumerate() {
+            *vk = 1 << ( SOBOL_BITS - 1 - k );
+        }
+        return v;
+    }
+
+    let (s, a, m) = SOBOL_PARAMETERS[dimension - 1];
+
+    for k in 0..SOBOL_BITS {
+        v[k] = if k < s {
+            m[k] << ( SOBOL_BITS - 1 - k )
+        } else {
+            let mut vk = v[k - s] ^ ( v[k - s] >> s );
+
+            for j in 1..s {
+                if ( a >> ( s - 1 - j ) ) & 1 == 1 {
+                    vk ^= v[k - j];
+                }
+            }
+
+            vk
+        };
+    }
+
+    v
+}
+
+
+/**
+ * Van der Corput radical inverse: mirrors the base b digits of index around the radix point
+ */
+fn radical_inverse(mut index: usize, base: usize) -> f64 {
+    let mut result = 0.;
+    let mut digit_scale = 1. / base as f64;
+
+    while index > 0 {
+        result += ( index % base ) as f64 * digit_scale;
+        index /= base;
+        digit_scale /= base as f64;
+    }
+
+    result
+}
+
+
+fn first_primes(count: usize) -> Vec<usize> {
+    let mut primes: Vec<usize> = Vec::with_capacity(count);
+    let mut candidate = 2;
+
+    while primes.len() < count {
+        if primes.iter().take_while(|p| *p * *p <= candidate).all(|p| candidate % p != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+
+    primes
+}
+
+
+/**
+ * Applies the product of one Gauss-Legendre rule per dimension, mapped onto the box
+ * Returns (value, evaluations)
+ */
+fn tensor_product(f: &ScalarFunction, bounds: &[(f64, f64)], rules: &[&QuadratureRule]) -> (f64, usize) {
+    let d = bounds.len();
+    let mut position = vec![0; d];
+    let mut x = vec![0.; d];
+    let mut value = 0.;
+    let mut evaluations = 0;
+
+    loop {
+        let mut weight = 1.;
+
+        for i in 0..d {
+            let (lower, upper) = bounds[i];
+            let half_length = ( upper - lower ) / 2.;
+            x[i] = ( lower + upper ) / 2. + half_length * rules[i].nodes[position[i]];
+            weight *= half_length * rules[i].weights[position[i]];
+        }
+
+        value += weight * f( &x );
+        evaluations += 1;
+
+        let Some(i) = (0..d).find(|i| position[*i] + 1 < rules[*i].nodes.len()) else {
+            break;
+        };
+
+        position[i] += 1;
+        position[..i].iter_mut().for_each(|p| *p = 0);
+    }
+
+    (value, evaluations)
+}
+
+
+/**
+ * Advances to the next multi-index l >= 1 with |l| <= q - d + 1 + (d - 1) = q, odometer order
+ * Returns false after the last one
+ */
+fn next_multi_index(index: &mut [usize], q: usize) -> bool {
+    let d = index.len();
+
+    for i in 0..d {
+        index[i] += 1;
+
+        if index.iter().sum::<usize>() <= q {
+            return true;
+        }
+
+        index[i] = 1;
+    }
+
+    false
+}
+
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1., |acc, i| acc * ( n - i ) as f64 / ( i + 1 ) as f64)
+}
+
+
+fn box_volume(bounds: &[(f64, f64)]) -> f64 {
+    bounds.iter().map(|(lower, upper)| upper - lower).product()
+}
+
+
+/**
+ * Welford's running mean and variance
+ */
+#[derive(Default)]
+struct RunningStatistics {
+    count: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl RunningStatistics {
+    fn push(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * ( x - self.mean );
+    }
+
+    /**
+     * Unbiased sample variance
+     */
+    fn variance(&self) -> f64 {
+        if self.count < 2 { 0. } else { self.m2 / ( self.count - 1 ) as f64 }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Integral of the monomial prod( x_i^powers[i] ) over the box
+     */
+    fn monomial_integral(powers: &[usize], bounds: &[(f64, f64)]) -> f64 {
+        powers.iter()
+            .zip(bounds)
+            .map(|(k, (a, b))| ( b.powi(*k as i32 + 1) - a.powi(*k as i32 + 1) ) / ( *k as f64 + 1. ))
+            .product()
+    }
+
+    #[test]
+    fn sparse_grid_is_exact_up_to_total_degree_2_level_minus_1() {
+        let bounds = [(0., 1.), (-1., 2.), (0.5, 1.5)];
+
+        for d in 2..=3 {
+            for level in 1..=4 {
+                let degree = 2 * level - 1;
+                let mut powers = vec![0; d];
+
+                loop {
+                    if powers.iter().sum::<usize>() <= degree {
+                        let monomial = |x: &[f64]| x.iter().zip(&powers).map(|(xi, k)| xi.powi(*k as i32)).product();
+                        let exact = monomial_integral(&powers, &bounds[..d]);
+                        let (value, _) = sparse_grid(&monomial, &bounds[..d], level);
+
+                        assert!(f64::abs(value - exact) <= 1e-12 * f64::max(1., f64::abs(exact)), "d = {d}, level = {level}, powers = {powers:?}");
+                    }
+
+                    let Some(i) = powers.iter().position(|k| *k < degree) else {
+                        break;
+                    };
+
+                    powers[i] += 1;
+                    powers[..i].iter_mut().for_each(|k| *k = 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sparse_grid_is_not_exact_beyond_total_degree_2_level_minus_1() {
+        let bounds = [(0., 1.), (0., 1.)];
+        let (value, _) = sparse_grid(&|x: &[f64]| x[0] * x[0] * x[1] * x[1], &bounds, 2);
+
+        assert!(f64::abs(value - 1. / 9.) > 1e-3);
+    }
+
+    #[test]
+    fn monte_carlo_estimates_are_within_their_standard_error() {
+        let bounds = [(0., 1.), (0., 2.)];
+        let f = |x: &[f64]| x[0] * x[0] + x[1] * x[1];
+        let exact = 2. / 3. + 8. / 3.;
+
+        let (value, standard_error) = monte_carlo(&f, &bounds, 100_000, 7);
+        assert!(standard_error < 1e-2);
+        assert!(f64::abs(value - exact) < 4. * standard_error);
+
+        let (value, stratified_error) = stratified_monte_carlo(&f, &bounds, 10, 100, 7);
+        assert!(stratified_error < standard_error);
+        assert!(f64::abs(value - exact) < 4. * stratified_error);
+    }
+
+    #[test]
+    fn quasi_monte_carlo_beats_monte_carlo_on_smooth_integrands() {
+        let bounds = [(0., 1.), (0., 1.), (0., 1.)];
+        let f = |x: &[f64]| f64::exp(x[0] + x[1] + x[2]);
+        let exact = f64::powi(std::f64::consts::E - 1., 3);
+        let samples = 1 << 14;
+
+        let (_, monte_carlo_error) = monte_carlo(&f, &bounds, samples, 3);
+
+        for sequence in [LowDiscrepancySequence::Halton, LowDiscrepancySequence::Sobol] {
+            let (value, standard_error) = quasi_monte_carlo(&f, &bounds, samples, sequence, 3);
+
+            assert!(standard_error < 0.2 * monte_carlo_error, "{sequence:?}");
+            assert!(f64::abs(value - exact) < 4. * standard_error + 1e-12, "{sequence:?}");
+        }
+    }
+
+    #[test]
+    fn halton_points_are_radical_inverses_in_prime_bases() {
+        let points = halton_points(2, 4);
+
+        assert_eq!(points, vec![
+            vec![1. / 2., 1. / 3.],
+            vec![1. / 4., 2. / 3.],
+            vec![3. / 4., 1. / 9.],
+            vec![1. / 8., 4. / 9.],
+        ]);
+    }
+
+    #[test]
+    fn sobol_points_stratify_every_coordinate() {
+        let d = 13;
+        let m = 8;
+        let points = sobol_points(d, 1 << m);
+
+        assert_eq!(points[..4].iter().map(|x| x[0]).collect::<Vec<f64>>(), vec![0., 0.5, 0.75, 0.25]);
+
+        // the first 2^m points put exactly one coordinate value in each interval [k / 2^m, (k + 1) / 2^m)
+        for j in 0..d {
+            let mut cells: Vec<usize> = points.iter().map(|x| ( x[j] * ( 1 << m ) as f64 ) as usize).collect();
+            cells.sort_unstable();
+
+            assert_eq!(cells, (0..1 << m).collect::<Vec<usize>>(), "dimension {}", j + 1);
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use std::vec;
 
-use super::helpers::{divdot, infinity_vecnorm, lu_decomp, matmat, matvec, max_abs_value_in_inferior_triangle, scalar_mul, subvec, tr};
-use crate::types::Matrix;
+use super::exact_methods::{det, solve_inf, solve_sup, LuFactorization};
+use super::iterative_methods::LinearOperator;
+use super::helpers::{cholesky_method, divdot, dot, get_identity_matrix, infinity_norm, infinity_vecnorm, lu_decomp, matmat, matvec, max_abs_value_in_inferior_triangle, scalar_mul, subvec, tr, transpose, vecnorm};
+use crate::types::{Matrix, Polynomial};
 
 
 /**
@@ -32,34 +34,226 @@ pub fn leverrier(a: &Matrix<f64>) -> Vec<f64> {
 }
 
 
+/**
+ * Results of the Faddeev-LeVerrier method
+ * characteristic_polynomial is p(lambda) = det(lambda I - A), so its leading coefficient is 1
+ * and its constant term is (-1)^n det(A)
+ * inverse is None when A is singular
+ */
+pub struct FaddeevLeverrier {
+    pub characteristic_polynomial: Polynomial,
+    pub adjugate: Matrix<f64>,
+    pub inverse: Option<Matrix<f64>>,
+    pub determinant: f64,
+}
+
+
+/**
+ * The Faddeev-LeVerrier method computes the characteristic polynomial together with
+ * the adjugate, the inverse and the determinant of A, from the matrices
+ * M[1] = I, c[0] = 1
+ * c[k] = -tr(A M[k]) / k, M[k + 1] = A M[k] + c[k] I, k = 1, ..., n
+ * adj(A) = (-1)^(n - 1) M[n], det(A) = (-1)^n c[n], A^-1 = -M[n] / c[n]
+ * Needs n matrix products, and the traces lose accuracy quickly as n grows
+ */
+pub fn faddeev_leverrier(a: &Matrix<f64>) -> FaddeevLeverrier {
+    let n = a.len();
+    let mut c = Vec::with_capacity(n + 1);
+    c.push(1.0);
+
+    let mut m = get_identity_matrix(n); // M[1]
+    let mut m_last = m.clone(); // M[n]
+
+    for k in 1..=n {
+        let a_m = matmat(a, &m);
+        let c_k = -tr(&a_m) / k as f64;
+        c.push(c_k);
+
+        m_last = m;
+        m = a_m;
+
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] += c_k;
+        }
+    }
+
+    let sign = if n.is_multiple_of(2) { 1.0 } else { -1.0 };
+    let determinant = sign * c[n];
+    let adjugate = scalar_mul_matrix(&m_last, -sign);
+    let inverse = if determinant != 0.0 { Some(scalar_mul_matrix(&m_last, -1.0 / c[n])) } else { None };
+
+    FaddeevLeverrier {
+        characteristic_polynomial: Polynomial { coefficients: c },
+        adjugate,
+        inverse,
+        determinant,
+    }
+}
+
+
+fn scalar_mul_matrix(a: &Matrix<f64>, scalar: f64) -> Matrix<f64> {
+    a.iter().map(|row| scalar_mul(row, scalar)).collect()
+}
+
+
+/**
+ * Options shared by the iterative eigenvalue methods
+ */
+pub struct EigenOptions {
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for EigenOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-10,
+            max_iterations: 1000,
+        }
+    }
+}
+
+
 /**
  * The power method is an iterative method to find the largest eigenvalue of a matrix
+ * y[k] = z[k] / ||z[k]||, z[k + 1] = A y[k]
+ * The eigenvalue is estimated by the Rayleigh quotient lambda = y^T A y, and the method stops when
+ * ||A y - lambda y|| < tolerance * |lambda| or after max_iterations
+ * Only products A y are needed, so A can be any LinearOperator, such as a sparse matrix
+ * Returns (eigenvalue, unit eigenvector, iterations)
+ */
+pub fn power_method<A: LinearOperator + ?Sized>(a: &A, options: &EigenOptions) -> (f64, Vec<f64>, usize) {
+    let n = a.dim();
+    let mut y = normalize(&vec![1.; n]); // y[0]
+    let mut lambda = 0.;
+
+    for k in 1..=options.max_iterations {
+        let z = a.apply( &y ); // z[k] = A*y[k-1]
+        lambda = dot( &y, &z );
+
+        if eigenpair_residual(&z, lambda, &y) <= options.tolerance * f64::abs(lambda) {
+            return (lambda, y, k);
+        }
+
+        let z_norm = vecnorm( &z );
+
+        if z_norm == 0. {
+            return (0., y, k);
+        }
+
+        y = scalar_mul( &z, 1. / z_norm ); // y[k] = z[k] / ||z[k]||
+    }
+
+    (lambda, y, options.max_iterations)
+}
+
+
+/**
+ * The inverse iteration finds the eigenvalue of smallest absolute value
+ * It is the power method applied to A^-1, solving Az = y with one LU factorization
+ * Returns (eigenvalue, unit eigenvector, iterations)
+ */
+pub fn inverse_iteration(a: &Matrix<f64>, options: &EigenOptions) -> (f64, Vec<f64>, usize) {
+    shifted_inverse_iteration(a, 0., options)
+}
+
+
+/**
+ * The shifted inverse iteration finds the eigenvalue closest to sigma
+ * It is the power method applied to (A - sigma I)^-1, factorized once
+ * Returns (eigenvalue, unit eigenvector, iterations)
  */
-pub fn power_method(a: &Matrix<f64>) -> f64 {
+pub fn shifted_inverse_iteration(a: &Matrix<f64>, sigma: f64, options: &EigenOptions) -> (f64, Vec<f64>, usize) {
     let n = a.len();
-    let epsilon = 1e-100;
+    let lu = shifted_factorization(a, sigma);
+    let mut y = normalize(&vec![1.; n]);
+    let mut lambda = sigma;
 
-    let mut y = vec![1.; n]; // y[0]
-    let mut z = matvec( a, &y ); // z[1]
+    for k in 1..=options.max_iterations {
+        y = normalize(&lu.solve(&y));
 
-    let mut l1 = z[0] / y[0];
-    let mut laux = l1;
+        let z = matvec(a, &y);
+        lambda = dot(&y, &z);
 
-    loop {
-        let alpha = infinity_vecnorm( &z );
-        y = scalar_mul( &z, 1. / alpha ); // y[k] = z[k] / alpha
-        z = matvec( a, &y ); // z[k + 1] = A*y[k]
+        if eigenpair_residual(&z, lambda, &y) <= options.tolerance * f64::max(f64::abs(lambda), 1.) {
+            return (lambda, y, k);
+        }
+    }
+
+    (lambda, y, options.max_iterations)
+}
 
-        l1 = z[0] / y[0];
 
-        if f64::abs( l1 - laux ) / f64::abs( laux ) < epsilon {
-            break;
+/**
+ * The Rayleigh quotient iteration updates the shift with the Rayleigh quotient at every step
+ * sigma[k] = y[k]^T A y[k], (A - sigma[k] I) z = y[k], y[k + 1] = z / ||z||
+ * It converges cubically for symmetric matrices, but each step needs a new LU factorization
+ * x0 is the starting vector, it defaults to [1, 1, ..., 1]
+ * Returns (eigenvalue, unit eigenvector, iterations)
+ */
+pub fn rayleigh_quotient_iteration(a: &Matrix<f64>, x0: Option<&[f64]>, options: &EigenOptions) -> (f64, Vec<f64>, usize) {
+    let n = a.len();
+    let mut y = normalize(&x0.map_or_else(|| vec![1.; n], |x| x.to_vec()));
+    let mut lambda = dot(&y, &matvec(a, &y));
+
+    for k in 1..=options.max_iterations {
+        y = normalize(&shifted_factorization(a, lambda).solve(&y));
+
+        let z = matvec(a, &y);
+        lambda = dot(&y, &z);
+
+        if eigenpair_residual(&z, lambda, &y) <= options.tolerance * f64::max(f64::abs(lambda), 1.) {
+            return (lambda, y, k);
         }
+    }
+
+    (lambda, y, options.max_iterations)
+}
+
+
+/**
+ * Returns A - sigma I
+ */
+fn shift(a: &Matrix<f64>, sigma: f64) -> Matrix<f64> {
+    let mut a_ = a.clone();
+
+    for (i, row) in a_.iter_mut().enumerate() {
+        row[i] -= sigma;
+    }
+
+    a_
+}
+
+
+/**
+ * Factorizes A - sigma I, moving sigma slightly away when it is exactly an eigenvalue
+ */
+fn shifted_factorization(a: &Matrix<f64>, sigma: f64) -> LuFactorization {
+    let mut sigma = sigma;
+    let perturbation = f64::EPSILON * f64::max(infinity_norm(a), 1.);
 
-        laux = l1
+    loop {
+        if let Some(lu) = LuFactorization::new(&shift(a, sigma)) {
+            return lu;
+        }
+
+        sigma += perturbation * f64::max(f64::abs(sigma), 1.);
     }
+}
+
+
+/**
+ * Returns ||z - lambda y||, where z = A y
+ */
+fn eigenpair_residual(z: &[f64], lambda: f64, y: &[f64]) -> f64 {
+    let r: Vec<f64> = z.iter().zip(y).map(|(zi, yi)| zi - lambda * yi).collect();
+    vecnorm(&r)
+}
+
 
-    l1
+fn normalize(v: &[f64]) -> Vec<f64> {
+    let norm = vecnorm(v);
+    v.iter().map(|el| el / norm).collect()
 }
 
 
@@ -86,3 +280,1522 @@ pub fn rutishauser(a: &Matrix<f64>) -> Vec<f64> {
         row[i as usize]
     }).collect()
 }
+
+
+/**
+ * Real Schur decomposition A = Z T Z^T
+ * T is quasi upper triangular: 1 x 1 diagonal blocks hold the real eigenvalues and
+ * 2 x 2 diagonal blocks hold the complex conjugate pairs
+ * eigenvalues[i] = (real part, imaginary part), in the order they appear on the diagonal of T
+ * z is only computed when the Schur vectors are accumulated
+ */
+pub struct SchurDecomposition {
+    pub eigenvalues: Vec<(f64, f64)>,
+    pub t: Matrix<f64>,
+    pub z: Option<Matrix<f64>>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+
+/**
+ * Reduces A to the upper Hessenberg form H = Q^T A Q with Householder reflections
+ * h[i][j] = 0 for i > j + 1
+ * Returns (H, Q), Q is orthogonal
+ */
+pub fn hessenberg(a: &Matrix<f64>) -> (Matrix<f64>, Matrix<f64>) {
+    let n = a.len();
+    let mut h = a.clone();
+    let mut q = get_identity_matrix(n);
+
+    for k in 0..n.saturating_sub(2) {
+        let x: Vec<f64> = ((k + 1)..n).map(|i| h[i][k]).collect();
+
+        if let Some((v, beta)) = householder(&x) {
+            reflect_rows(&mut h, &v, beta, k + 1, k, n);
+            reflect_columns(&mut h, &v, beta, k + 1, 0, n);
+            reflect_columns(&mut q, &v, beta, k + 1, 0, n);
+
+            for row in h.iter_mut().skip(k + 2) {
+                row[k] = 0.;
+            }
+        }
+    }
+
+    (h, q)
+}
+
+
+/**
+ * Computes all the eigenvalues of a real matrix with the implicitly shifted QR algorithm
+ * The matrix is reduced to the Hessenberg form, then Francis double shift steps are applied
+ * to the active block, deflating whenever a subdiagonal element satisfies
+ * |h[k][k - 1]| <= tolerance * ( |h[k - 1][k - 1]| + |h[k][k]| )
+ * Complex conjugate pairs are found in real arithmetic through the 2 x 2 blocks
+ * max_iterations bounds the total number of QR steps, converged is false when it is reached
+ */
+pub fn francis_qr(a: &Matrix<f64>, accumulate_schur_vectors: bool, options: &EigenOptions) -> SchurDecomposition {
+    let n = a.len();
+    let (mut h, q) = hessenberg(a);
+    let mut z = if accumulate_schur_vectors { Some(q) } else { None };
+    let norm = f64::max(infinity_norm(&h), f64::MIN_POSITIVE);
+
+    let mut iterations = 0;
+    let mut iterations_since_deflation = 0;
+    let mut converged = true;
+    let mut hi = n;
+
+    while hi > 0 {
+        let last = hi - 1;
+        let mut lo = last;
+
+        // looks for a negligible subdiagonal element
+        while lo > 0 {
+            let mut s = f64::abs(h[lo - 1][lo - 1]) + f64::abs(h[lo][lo]);
+
+            if s == 0. {
+                s = norm;
+            }
+
+            if f64::abs(h[lo][lo - 1]) <= options.tolerance * s {
+                h[lo][lo - 1] = 0.;
+                break;
+            }
+
+            lo -= 1;
+        }
+
+        if lo == last {
+            hi -= 1;
+            iterations_since_deflation = 0;
+            continue;
+        }
+
+        if lo + 1 == last {
+            standardize_block(&mut h, z.as_mut(), lo);
+            hi -= 2;
+            iterations_since_deflation = 0;
+            continue;
+        }
+
+        if iterations >= options.max_iterations {
+            converged = false;
+            break;
+        }
+
+        iterations += 1;
+        iterations_since_deflation += 1;
+
+        francis_step(&mut h, z.as_mut(), lo, last, iterations_since_deflation);
+    }
+
+    let eigenvalues = schur_eigenvalues(&h);
+
+    SchurDecomposition { eigenvalues, t: h, z, iterations, converged }
+}
+
+
+/**
+ * One Francis double shift step on the active block h[lo..=hi][lo..=hi]
+ * The shifts are the eigenvalues of the trailing 2 x 2 block, except every 10 steps
+ * without deflation, when exceptional shifts are used to break cycles
+ */
+fn francis_step(h: &mut Matrix<f64>, mut z: Option<&mut Matrix<f64>>, lo: usize, hi: usize, iterations_since_deflation: usize) {
+    let n = h.len();
+    let m = hi - 1;
+
+    let (s, t) = if iterations_since_deflation.is_multiple_of(10) {
+        let w = f64::abs(h[hi][m]) + f64::abs(h[m][m - 1]);
+        (1.5 * w, w * w)
+    } else {
+        (h[m][m] + h[hi][hi], h[m][m] * h[hi][hi] - h[m][hi] * h[hi][m])
+    };
+
+    // first column of (H - s1 I)(H - s2 I)
+    let mut x = h[lo][lo] * h[lo][lo] + h[lo][lo + 1] * h[lo + 1][lo] - s * h[lo][lo] + t;
+    let mut y = h[lo + 1][lo] * (h[lo][lo] + h[lo + 1][lo + 1] - s);
+    let mut w = h[lo + 1][lo] * h[lo + 2][lo + 1];
+
+    // chases the bulge down the subdiagonal
+    for k in lo..(hi - 1) {
+        if let Some((v, beta)) = householder(&[x, y, w]) {
+            let first_col = if k > lo { k - 1 } else { lo };
+            reflect_rows(h, &v, beta, k, first_col, n);
+            reflect_columns(h, &v, beta, k, 0, usize::min(k + 4, hi + 1));
+
+            if let Some(z) = z.as_deref_mut() {
+                reflect_columns(z, &v, beta, k, 0, n);
+            }
+
+            if k > lo {
+                h[k + 1][k - 1] = 0.;
+                h[k + 2][k - 1] = 0.;
+            }
+        }
+
+        x = h[k + 1][k];
+        y = h[k + 2][k];
+
+        if k + 3 <= hi {
+            w = h[k + 3][k];
+        }
+    }
+
+    if let Some((v, beta)) = householder(&[x, y]) {
+        reflect_rows(h, &v, beta, hi - 1, hi - 2, n);
+        reflect_columns(h, &v, beta, hi - 1, 0, hi + 1);
+
+        if let Some(z) = z {
+            reflect_columns(z, &v, beta, hi - 1, 0, n);
+        }
+
+        h[hi][hi - 2] = 0.;
+    }
+}
+
+
+/**
+ * Splits a deflated 2 x 2 block h[k..=k + 1][k..=k + 1] with real eigenvalues into
+ * two 1 x 1 blocks with a rotation, so T is triangular wherever the spectrum is real
+ */
+fn standardize_block(h: &mut Matrix<f64>, z: Option<&mut Matrix<f64>>, k: usize) {
+    let n = h.len();
+    let (a, b, c, d) = (h[k][k], h[k][k + 1], h[k + 1][k], h[k + 1][k + 1]);
+
+    if c == 0. {
+        return;
+    }
+
+    let p = 0.5 * (a - d);
+    let discriminant = p * p + b * c;
+
+    if discriminant < 0. {
+        return;
+    }
+
+    // eigenvector (lambda - d, c) of the block, for the eigenvalue farther from d
+    let lambda = d + p + f64::sqrt(discriminant).copysign(p);
+    let r = f64::hypot(lambda - d, c);
+    let (cs, sn) = ((lambda - d) / r, c / r);
+
+    let (top, bottom) = h.split_at_mut(k + 1);
+
+    for (u, v) in top[k][k..].iter_mut().zip(bottom[0][k..].iter_mut()) {
+        (*u, *v) = (cs * *u + sn * *v, -sn * *u + cs * *v);
+    }
+
+    for row in h.iter_mut().take(k + 2) {
+        let (u, v) = (row[k], row[k + 1]);
+        row[k] = cs * u + sn * v;
+        row[k + 1] = -sn * u + cs * v;
+    }
+
+    if let Some(z) = z {
+        for row in z.iter_mut() {
+            let (u, v) = (row[k], row[k + 1]);
+            row[k] = cs * u + sn * v;
+            row[k + 1] = -sn * u + cs * v;
+        }
+    }
+
+    h[k + 1][k] = 0.;
+}
+
+
+/**
+ * Reads the eigenvalues from the diagonal blocks of a quasi upper triangular matrix
+ */
+fn schur_eigenvalues(t: &Matrix<f64>) -> Vec<(f64, f64)> {
+    let n = t.len();
+    let mut eigenvalues = Vec::with_capacity(n);
+    let mut k = 0;
+
+    while k < n {
+        if k + 1 < n && t[k + 1][k] != 0. {
+            let (a, b, c, d) = (t[k][k], t[k][k + 1], t[k + 1][k], t[k + 1][k + 1]);
+            let p = 0.5 * (a - d);
+            let discriminant = p * p + b * c;
+
+            if discriminant < 0. {
+                let im = f64::sqrt(-discriminant);
+                eigenvalues.push((d + p, im));
+                eigenvalues.push((d + p, -im));
+            } else {
+                let root = f64::sqrt(discriminant);
+                eigenvalues.push((d + p + root, 0.));
+                eigenvalues.push((d + p - root, 0.));
+            }
+
+            k += 2;
+        } else {
+            eigenvalues.push((t[k][k], 0.));
+            k += 1;
+        }
+    }
+
+    eigenvalues
+}
+
+
+/**
+ * Returns (v, beta) such that (I - beta v v^T) x = -+||x|| e1, or None when x = 0
+ */
+fn householder(x: &[f64]) -> Option<(Vec<f64>, f64)> {
+    let alpha = vecnorm(x);
+
+    if alpha == 0. {
+        return None;
+    }
+
+    let mut v = x.to_vec();
+    v[0] += alpha.copysign(x[0]);
+
+    let v_v = dot(&v, &v);
+
+    Some((v, 2. / v_v))
+}
+
+
+/**
+ * a = (I - beta v v^T) a, acting on the rows offset..offset + v.len() and the columns first_col..last_col
+ */
+fn reflect_rows(a: &mut Matrix<f64>, v: &[f64], beta: f64, offset: usize, first_col: usize, last_col: usize) {
+    let rows = &mut a[offset..(offset + v.len())];
+    let mut s = vec![0.; last_col - first_col]; // s = beta v^T a
+
+    for (row, vi) in rows.iter().zip(v) {
+        for (sj, el) in s.iter_mut().zip(&row[first_col..last_col]) {
+            *sj += beta * vi * el;
+        }
+    }
+
+    for (row, vi) in rows.iter_mut().zip(v) {
+        for (el, sj) in row[first_col..last_col].iter_mut().zip(&s) {
+            *el -= sj * vi;
+        }
+    }
+}
+
+
+/**
+ * a = a (I - beta v v^T), acting on the columns offset..offset + v.len() and the rows first_row..last_row
+ */
+fn reflect_columns(a: &mut Matrix<f64>, v: &[f64], beta: f64, offset: usize, first_row: usize, last_row: usize) {
+    for row in a.iter_mut().take(last_row).skip(first_row) {
+        let s = dot(&row[offset..(offset + v.len())], v) * beta;
+
+        for (el, vi) in row[offset..(offset + v.len())].iter_mut().zip(v) {
+            *el -= s * vi;
+        }
+    }
+}
+
+
+/**
+ * Eigenpairs of a symmetric matrix, A = V diag(eigenvalues) V^T
+ * eigenvalues are sorted in increasing order and the column k of eigenvectors
+ * is the unit eigenvector of eigenvalues[k], so V is orthogonal
+ */
+pub struct SymmetricEigen {
+    pub eigenvalues: Vec<f64>,
+    pub eigenvectors: Matrix<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+
+/**
+ * The cyclic Jacobi method diagonalizes a symmetric matrix with plane rotations,
+ * annihilating every off diagonal element a[p][q], p < q, once per sweep
+ * Stops when off(A) = sqrt( sum(a[i][j]², i != j) ) <= tolerance * ||A||_F,
+ * max_iterations bounds the number of sweeps
+ */
+pub fn jacobi_eigen(a: &Matrix<f64>, options: &EigenOptions) -> SymmetricEigen {
+    let n = a.len();
+    let mut a_ = a.clone();
+    let mut v = get_identity_matrix(n);
+    let frobenius = f64::sqrt(a.iter().flatten().map(|el| el * el).sum::<f64>());
+
+    let mut sweeps = 0;
+    let mut converged = false;
+
+    loop {
+        let off: f64 = (0..n).map(|i| (0..n).filter(|j| *j != i).map(|j| a_[i][j] * a_[i][j]).sum::<f64>()).sum();
+
+        if f64::sqrt(off) <= options.tolerance * frobenius {
+            converged = true;
+            break;
+        }
+
+        if sweeps >= options.max_iterations {
+            break;
+        }
+
+        sweeps += 1;
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a_[p][q] == 0. {
+                    continue;
+                }
+
+                // chooses the smallest rotation angle, tan(theta) = t
+                let theta = (a_[q][q] - a_[p][p]) / (2. * a_[p][q]);
+                let t = 1f64.copysign(theta) / (f64::abs(theta) + f64::hypot(theta, 1.));
+                let c = 1. / f64::hypot(t, 1.);
+                let s = t * c;
+
+                rotate_columns(&mut a_, p, q, c, s);
+                rotate_rows(&mut a_, p, q, c, s);
+                rotate_columns(&mut v, p, q, c, s);
+
+                a_[p][q] = 0.;
+                a_[q][p] = 0.;
+            }
+        }
+    }
+
+    let eigenvalues = (0..n).map(|i| a_[i][i]).collect();
+
+    sorted_symmetric_eigen(eigenvalues, v, sweeps, converged)
+}
+
+
+/**
+ * Computes all the eigenpairs of a symmetric matrix in two stages:
+ *  - Householder tridiagonalization, Q^T A Q = T (the Hessenberg form of a symmetric matrix)
+ *  - implicit QL iterations with Wilkinson shifts on T, accumulated into Q
+ *
+ * An off diagonal element e[m] is neglected when |e[m]| <= tolerance * ( |d[m]| + |d[m + 1]| ),
+ * max_iterations bounds the total number of QL steps
+ */
+pub fn symmetric_eigen(a: &Matrix<f64>, options: &EigenOptions) -> SymmetricEigen {
+    let n = a.len();
+    let (t, mut z) = hessenberg(a);
+
+    let mut d: Vec<f64> = (0..n).map(|i| t[i][i]).collect();
+    let mut e: Vec<f64> = (0..n).map(|i| if i + 1 < n { t[i + 1][i] } else { 0. }).collect();
+
+    let mut iterations = 0;
+    let mut converged = true;
+
+    'eigenvalues: for l in 0..n {
+        loop {
+            let mut m = l;
+
+            while m + 1 < n {
+                if f64::abs(e[m]) <= options.tolerance * (f64::abs(d[m]) + f64::abs(d[m + 1])) {
+                    break;
+                }
+
+                m += 1;
+            }
+
+            if m == l {
+                break;
+            }
+
+            if iterations >= options.max_iterations {
+                converged = false;
+                break 'eigenvalues;
+            }
+
+            iterations += 1;
+
+            // Wilkinson shift from the leading 2 x 2 block of the unreduced part
+            let mut g = (d[l + 1] - d[l]) / (2. * e[l]);
+            let mut r = f64::hypot(g, 1.);
+            g = d[m] - d[l] + e[l] / (g + r.copysign(g));
+
+            let (mut s, mut c, mut p) = (1., 1., 0.);
+            let mut split = false;
+
+            for i in (l..m).rev() {
+                let f = s * e[i];
+                let b = c * e[i];
+                r = f64::hypot(f, g);
+                e[i + 1] = r;
+
+                if r == 0. {
+                    // the matrix split, the rotations so far already reduced it
+                    d[i + 1] -= p;
+                    e[m] = 0.;
+                    split = true;
+                    break;
+                }
+
+                s = f / r;
+                c = g / r;
+                g = d[i + 1] - p;
+                r = (d[i] - g) * s + 2. * c * b;
+                p = s * r;
+                d[i + 1] = g + p;
+                g = c * r - b;
+
+                for row in z.iter_mut() {
+                    let f = row[i + 1];
+                    row[i + 1] = s * row[i] + c * f;
+                    row[i] = c * row[i] - s * f;
+                }
+            }
+
+            if !split {
+                d[l] -= p;
+                e[l] = g;
+                e[m] = 0.;
+            }
+        }
+    }
+
+    sorted_symmetric_eigen(d, z, iterations, converged)
+}
+
+
+/**
+ * Sorts the eigenvalues in increasing order, moving the eigenvector columns along
+ */
+fn sorted_symmetric_eigen(eigenvalues: Vec<f64>, v: Matrix<f64>, iterations: usize, converged: bool) -> SymmetricEigen {
+    let mut order: Vec<usize> = (0..eigenvalues.len()).collect();
+    order.sort_by(|i, j| eigenvalues[*i].total_cmp(&eigenvalues[*j]));
+
+    SymmetricEigen {
+        eigenvalues: order.iter().map(|k| eigenvalues[*k]).collect(),
+        eigenvectors: v.iter().map(|row| order.iter().map(|k| row[*k]).collect()).collect(),
+        iterations,
+        converged,
+    }
+}
+
+
+/**
+ * a = a G, where G is the rotation by (c, s) in the plane (p, q)
+ */
+pub(crate) fn rotate_columns(a: &mut Matrix<f64>, p: usize, q: usize, c: f64, s: f64) {
+    for row in a.iter_mut() {
+        let (u, v) = (row[p], row[q]);
+        row[p] = c * u - s * v;
+        row[q] = s * u + c * v;
+    }
+}
+
+
+/**
+ * a = G^T a, where G is the rotation by (c, s) in the plane (p, q)
+ */
+fn rotate_rows(a: &mut Matrix<f64>, p: usize, q: usize, c: f64, s: f64) {
+    let (top, bottom) = a.split_at_mut(q);
+
+    for (u, v) in top[p].iter_mut().zip(bottom[0].iter_mut()) {
+        (*u, *v) = (c * *u - s * *v, s * *u + c * *v);
+    }
+}
+
+
+/**
+ * Gershgorin disc { z in C : |z - center| <= radius }
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct GershgorinDisc {
+    pub center: f64,
+    pub radius: f64,
+}
+
+impl GershgorinDisc {
+    pub fn contains(&self, re: f64, im: f64) -> bool {
+        f64::hypot(re - self.center, im) <= self.radius
+    }
+}
+
+
+/**
+ * Row discs: center a[i][i], radius sum(|a[i][j]|, j != i)
+ * Every eigenvalue lies in the union of the discs
+ */
+pub fn gershgorin_row_discs(a: &Matrix<f64>) -> Vec<GershgorinDisc> {
+    a.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let radius = row.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, el)| f64::abs(*el)).sum();
+            GershgorinDisc { center: row[i], radius }
+        })
+        .collect()
+}
+
+
+/**
+ * Column discs: center a[j][j], radius sum(|a[i][j]|, i != j)
+ * These are the row discs of A^T, which has the same eigenvalues
+ */
+pub fn gershgorin_column_discs(a: &Matrix<f64>) -> Vec<GershgorinDisc> {
+    gershgorin_row_discs(&transpose(a))
+}
+
+
+/**
+ * Returns (lower, upper) bounds of the spectral radius rho(A)
+ * upper = min( ||A||_1, ||A||_inf, ||A||_F ), since rho(A) <= ||A|| for these norms
+ * lower = max( |tr(A)| / n, |det(A)|^(1 / n) ), from the mean and the geometric mean of |lambda|
+ */
+pub fn spectral_radius_bounds(a: &Matrix<f64>) -> (f64, f64) {
+    let n = a.len();
+
+    if n == 0 {
+        return (0., 0.);
+    }
+
+    let norm_1 = infinity_norm(&transpose(a));
+    let norm_inf = infinity_norm(a);
+    let norm_f = f64::sqrt(a.iter().flatten().map(|el| el * el).sum::<f64>());
+    let upper = norm_1.min(norm_inf).min(norm_f);
+
+    let lower = f64::max(f64::abs(tr(a)) / n as f64, f64::abs(det(a)).powf(1. / n as f64));
+
+    (lower, upper)
+}
+
+
+/**
+ * Regions of the complex plane that contain every eigenvalue of A
+ *  - each eigenvalue lies in the union of the row discs and in the union of the column discs
+ *  - real_interval bounds the real parts, from the intersection of the hulls of both unions
+ *  - the modulus is bounded by spectral_radius_bounds.1
+ */
+pub struct InclusionRegions {
+    pub row_discs: Vec<GershgorinDisc>,
+    pub column_discs: Vec<GershgorinDisc>,
+    pub real_interval: (f64, f64),
+    pub spectral_radius_bounds: (f64, f64),
+}
+
+impl InclusionRegions {
+    /**
+     * Checks whether lambda = re + i im satisfies all the inclusion conditions,
+     * a cheap test for the results of the iterative eigen-solvers
+     */
+    pub fn contains(&self, re: f64, im: f64) -> bool {
+        let slack = 1e-12 * f64::max(self.spectral_radius_bounds.1, 1.);
+
+        self.row_discs.iter().any(|disc| f64::hypot(re - disc.center, im) <= disc.radius + slack)
+            && self.column_discs.iter().any(|disc| f64::hypot(re - disc.center, im) <= disc.radius + slack)
+            && self.real_interval.0 - slack <= re
+            && re <= self.real_interval.1 + slack
+            && f64::hypot(re, im) <= self.spectral_radius_bounds.1 + slack
+    }
+}
+
+
+/**
+ * Computes the Gershgorin discs and the norm bounds of the eigenvalues of A,
+ * useful to choose shifts for the inverse iteration and to validate computed eigenvalues
+ */
+pub fn eigenvalue_inclusion_regions(a: &Matrix<f64>) -> InclusionRegions {
+    let row_discs = gershgorin_row_discs(a);
+    let column_discs = gershgorin_column_discs(a);
+
+    let hull = |discs: &[GershgorinDisc]| {
+        discs.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), disc| {
+            (lo.min(disc.center - disc.radius), hi.max(disc.center + disc.radius))
+        })
+    };
+
+    let (row_lo, row_hi) = hull(&row_discs);
+    let (col_lo, col_hi) = hull(&column_discs);
+    let spectral_radius_bounds = spectral_radius_bounds(a);
+    let rho = spectral_radius_bounds.1;
+
+    InclusionRegions {
+        row_discs,
+        column_discs,
+        real_interval: (row_lo.max(col_lo).max(-rho), row_hi.min(col_hi).min(rho)),
+        spectral_radius_bounds,
+    }
+}
+
+
+/**
+ * Solves the symmetric definite generalized eigenvalue problem A x = lambda B x,
+ * A symmetric and B symmetric positive definite
+ * With B = G G^T (Cholesky), it is the standard problem C y = lambda y, C = G^-1 A G^-T, x = G^-T y
+ * The eigenvectors are B-orthonormal, X^T B X = I
+ * Returns None when the Cholesky factorization of B breaks down
+ */
+pub fn symmetric_generalized_eigen(a: &Matrix<f64>, b: &Matrix<f64>, options: &EigenOptions) -> Option<SymmetricEigen> {
+    let n = a.len();
+    let (g, gt) = cholesky_method(b);
+
+    if (0..n).any(|i| g[i][i] <= 0. || !g[i][i].is_finite()) {
+        return None;
+    }
+
+    // C = G^-1 (G^-1 A)^T, since A is symmetric
+    let g_inv_a_t: Matrix<f64> = transpose(a).iter().map(|col| solve_inf(&g, col)).collect(); // (G^-1 A)^T
+    let c_t: Matrix<f64> = transpose(&g_inv_a_t).iter().map(|col| solve_inf(&g, col)).collect();
+    let c: Matrix<f64> = (0..n).map(|i| (0..n).map(|j| 0.5 * (c_t[i][j] + c_t[j][i])).collect()).collect();
+
+    let SymmetricEigen { eigenvalues, eigenvectors, iterations, converged } = symmetric_eigen(&c, options);
+    let x_columns: Matrix<f64> = transpose(&eigenvectors).iter().map(|y| solve_sup(&gt, y)).collect();
+
+    Some(SymmetricEigen { eigenvalues, eigenvectors: transpose(&x_columns), iterations, converged })
+}
+
+
+/**
+ * Generalized real Schur decomposition of the pencil (A, B)
+ * S = Q^T A Z is quasi upper triangular and T = Q^T B Z is upper triangular
+ * eigenvalues[i] = (real part, imaginary part) of the solutions of det(A - lambda B) = 0,
+ * in the order of the diagonal blocks, (inf, 0) marks an infinite eigenvalue (a zero of B)
+ * Column k of eigenvectors is a right eigenvector of eigenvalues[k], A x = lambda B x (B x = 0 when lambda is infinite),
+ * for a complex pair at k, k + 1 the columns k and k + 1 hold the real and imaginary parts of the eigenvector
+ * of the eigenvalue with a positive imaginary part, the other one is its conjugate
+ */
+pub struct GeneralizedSchur {
+    pub eigenvalues: Vec<(f64, f64)>,
+    pub eigenvectors: Matrix<f64>,
+    pub s: Matrix<f64>,
+    pub t: Matrix<f64>,
+    pub q: Matrix<f64>,
+    pub z: Matrix<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+
+/**
+ * Solves the generalized eigenvalue problem A x = lambda B x for general square pairs with the QZ algorithm
+ *  - Hessenberg-triangular reduction: A upper Hessenberg and B upper triangular with orthogonal Q, Z
+ *  - implicit double shift QZ steps (Moler-Stewart), the Francis steps applied to A B^-1
+ *    without forming the inverse
+ *
+ * Deflates when |a[k][k - 1]| <= tolerance * ( |a[k - 1][k - 1]| + |a[k][k]| ), and moves
+ * zero diagonal elements of B to the bottom of the active block as infinite eigenvalues
+ * max_iterations bounds the total number of QZ steps
+ */
+pub fn qz(a: &Matrix<f64>, b: &Matrix<f64>, options: &EigenOptions) -> GeneralizedSchur {
+    let n = a.len();
+    let (mut s, mut t, mut q, mut z) = hessenberg_triangular(a, b);
+    let b_norm = f64::max(infinity_norm(&t), f64::MIN_POSITIVE);
+    let a_norm = f64::max(infinity_norm(&s), f64::MIN_POSITIVE);
+
+    let mut iterations = 0;
+    let mut iterations_since_deflation = 0;
+    let mut converged = true;
+    let mut hi = n;
+
+    while hi > 0 {
+        let last = hi - 1;
+        let mut lo = last;
+
+        while lo > 0 {
+            let mut scale = f64::abs(s[lo - 1][lo - 1]) + f64::abs(s[lo][lo]);
+
+            if scale == 0. {
+                scale = a_norm;
+            }
+
+            if f64::abs(s[lo][lo - 1]) <= options.tolerance * scale {
+                s[lo][lo - 1] = 0.;
+                break;
+            }
+
+            lo -= 1;
+        }
+
+        if lo == last {
+            hi -= 1;
+            iterations_since_deflation = 0;
+            continue;
+        }
+
+        if let Some(k) = (lo..=last).find(|k| f64::abs(t[*k][*k]) <= f64::EPSILON * b_norm) {
+            t[k][k] = 0.;
+            push_infinite_eigenvalue_down(&mut s, &mut t, &mut q, &mut z, lo, last, k);
+            continue;
+        }
+
+        if lo + 1 == last {
+            hi -= 2;
+            iterations_since_deflation = 0;
+            continue;
+        }
+
+        if iterations >= options.max_iterations {
+            converged = false;
+            break;
+        }
+
+        iterations += 1;
+        iterations_since_deflation += 1;
+
+        qz_step(&mut s, &mut t, &mut q, &mut z, lo, last, iterations_since_deflation);
+    }
+
+    let eigenvalues = generalized_eigenvalues(&s, &t);
+    let eigenvectors = generalized_eigenvectors(&s, &t, &z, &eigenvalues);
+
+    GeneralizedSchur { eigenvalues, eigenvectors, s, t, q, z, iterations, converged }
+}
+
+
+/**
+ * Reduces (A, B) to (Q^T A Z, Q^T B Z) with the first upper Hessenberg and the second upper triangular
+ * Returns (S, T, Q, Z)
+ */
+fn hessenberg_triangular(a: &Matrix<f64>, b: &Matrix<f64>) -> (Matrix<f64>, Matrix<f64>, Matrix<f64>, Matrix<f64>) {
+    let n = a.len();
+    let mut s = a.clone();
+    let mut t = b.clone();
+    let mut q = get_identity_matrix(n);
+    let mut z = get_identity_matrix(n);
+
+    // QR decomposition of B
+    for k in 0..n.saturating_sub(1) {
+        let x: Vec<f64> = (k..n).map(|i| t[i][k]).collect();
+
+        if let Some((v, beta)) = householder(&x) {
+            reflect_rows(&mut t, &v, beta, k, 0, n);
+            reflect_rows(&mut s, &v, beta, k, 0, n);
+            reflect_columns(&mut q, &v, beta, k, 0, n);
+
+            for row in t.iter_mut().skip(k + 1) {
+                row[k] = 0.;
+            }
+        }
+    }
+
+    // annihilates A below the subdiagonal, column by column from the bottom, keeping B triangular
+    for j in 0..n.saturating_sub(2) {
+        for i in ((j + 2)..n).rev() {
+            let (x, y) = (s[i - 1][j], s[i][j]);
+            left_rotation(&mut s, &mut t, &mut q, i - 1, x, y);
+            s[i][j] = 0.;
+
+            let (x, y) = (t[i][i - 1], t[i][i]);
+            right_rotation(&mut s, &mut t, &mut z, i - 1, x, y);
+            t[i][i - 1] = 0.;
+        }
+    }
+
+    (s, t, q, z)
+}
+
+
+/**
+ * One implicit double shift QZ step on the active block lo..=hi
+ * The shifts are the eigenvalues of the trailing 2 x 2 block of A B^-1,
+ * exceptional shifts are used every 10 steps without deflation
+ */
+fn qz_step(s: &mut Matrix<f64>, t: &mut Matrix<f64>, q: &mut Matrix<f64>, z: &mut Matrix<f64>, lo: usize, hi: usize, iterations_since_deflation: usize) {
+    let n = s.len();
+    let m = hi - 1;
+
+    let (shift_sum, shift_product) = if iterations_since_deflation.is_multiple_of(10) {
+        let w = f64::abs(s[hi][m] / t[m][m]) + f64::abs(s[m][m - 1] / t[m - 1][m - 1]);
+        (1.5 * w, w * w)
+    } else {
+        // trailing 2 x 2 block of A B^-1, the trailing block of B^-1 is the inverse of the trailing block of B
+        let k0 = hi - 2;
+        let t_inv = upper_triangular_inverse_3(t, k0);
+        let h = |r: usize, c: usize| (k0..=c).map(|k| s[r][k] * t_inv[k - k0][c - k0]).sum::<f64>();
+        let (h11, h12, h21, h22) = (h(m, m), h(m, hi), h(hi, m), h(hi, hi));
+        (h11 + h22, h11 * h22 - h12 * h21)
+    };
+
+    // first column of (A B^-1)² - shift_sum A B^-1 + shift_product I, only three entries are nonzero
+    let u0 = 1. / t[lo][lo]; // B^-1 e1
+    let au = [s[lo][lo] * u0, s[lo + 1][lo] * u0];
+    let w1 = au[1] / t[lo + 1][lo + 1];
+    let w0 = (au[0] - t[lo][lo + 1] * w1) / t[lo][lo];
+    let mut x = s[lo][lo] * w0 + s[lo][lo + 1] * w1 - shift_sum * au[0] + shift_product;
+    let mut y = s[lo + 1][lo] * w0 + s[lo + 1][lo + 1] * w1 - shift_sum * au[1];
+    let mut w = s[lo + 2][lo + 1] * w1;
+
+    for k in lo..(hi - 1) {
+        // restores the Hessenberg form of A, the bulge moves to B
+        if let Some((v, beta)) = householder(&[x, y, w]) {
+            reflect_rows(s, &v, beta, k, 0, n);
+            reflect_rows(t, &v, beta, k, 0, n);
+            reflect_columns(q, &v, beta, k, 0, n);
+
+            if k > lo {
+                s[k + 1][k - 1] = 0.;
+                s[k + 2][k - 1] = 0.;
+            }
+        }
+
+        // restores the triangular form of B, zeroing t[k + 2][k] and t[k + 2][k + 1], then t[k + 1][k]
+        if let Some((v, beta)) = householder_to_last(&[t[k + 2][k], t[k + 2][k + 1], t[k + 2][k + 2]]) {
+            reflect_columns(s, &v, beta, k, 0, n);
+            reflect_columns(t, &v, beta, k, 0, n);
+            reflect_columns(z, &v, beta, k, 0, n);
+            t[k + 2][k] = 0.;
+            t[k + 2][k + 1] = 0.;
+        }
+
+        let (tx, ty) = (t[k + 1][k], t[k + 1][k + 1]);
+        right_rotation(s, t, z, k, tx, ty);
+        t[k + 1][k] = 0.;
+
+        x = s[k + 1][k];
+        y = s[k + 2][k];
+
+        if k + 3 <= hi {
+            w = s[k + 3][k];
+        }
+    }
+
+    left_rotation(s, t, q, hi - 1, x, y);
+    s[hi][hi - 2] = 0.;
+
+    let (tx, ty) = (t[hi][hi - 1], t[hi][hi]);
+    right_rotation(s, t, z, hi - 1, tx, ty);
+    t[hi][hi - 1] = 0.;
+}
+
+
+/**
+ * Moves the zero t[k][k] to t[hi][hi] with rotations, then zeroes s[hi][hi - 1],
+ * so that (s[hi][hi], 0) deflates as an infinite eigenvalue
+ */
+fn push_infinite_eigenvalue_down(s: &mut Matrix<f64>, t: &mut Matrix<f64>, q: &mut Matrix<f64>, z: &mut Matrix<f64>, lo: usize, hi: usize, k: usize) {
+    for i in k..hi {
+        let (x, y) = (t[i][i + 1], t[i + 1][i + 1]);
+        left_rotation(s, t, q, i, x, y);
+        t[i + 1][i + 1] = 0.;
+
+        if i > lo {
+            let (x, y) = (s[i + 1][i - 1], s[i + 1][i]);
+            right_rotation(s, t, z, i - 1, x, y);
+            s[i + 1][i - 1] = 0.;
+        }
+    }
+
+    let (x, y) = (s[hi][hi - 1], s[hi][hi]);
+    right_rotation(s, t, z, hi - 1, x, y);
+    s[hi][hi - 1] = 0.;
+}
+
+
+/**
+ * Rotates the rows i and i + 1 of S and T so that (x, y) becomes (r, 0), accumulating into Q
+ */
+fn left_rotation(s: &mut Matrix<f64>, t: &mut Matrix<f64>, q: &mut Matrix<f64>, i: usize, x: f64, y: f64) {
+    let r = f64::hypot(x, y);
+
+    if r == 0. {
+        return;
+    }
+
+    let (c, sn) = (x / r, y / r);
+    rotate_rows(s, i, i + 1, c, -sn);
+    rotate_rows(t, i, i + 1, c, -sn);
+    rotate_columns(q, i, i + 1, c, -sn);
+}
+
+
+/**
+ * Rotates the columns i and i + 1 of S and T so that the row entries (x, y) become (0, r), accumulating into Z
+ */
+fn right_rotation(s: &mut Matrix<f64>, t: &mut Matrix<f64>, z: &mut Matrix<f64>, i: usize, x: f64, y: f64) {
+    let r = f64::hypot(x, y);
+
+    if r == 0. {
+        return;
+    }
+
+    let (c, sn) = (y / r, x / r);
+    rotate_columns(s, i, i + 1, c, sn);
+    rotate_columns(t, i, i + 1, c, sn);
+    rotate_columns(z, i, i + 1, c, sn);
+}
+
+
+/**
+ * Householder reflection that maps x to a multiple of the last canonical vector,
+ * used from the right to zero the leading entries of a row
+ */
+fn householder_to_last(x: &[f64]) -> Option<(Vec<f64>, f64)> {
+    let reversed: Vec<f64> = x.iter().rev().copied().collect();
+    householder(&reversed).map(|(v, beta)| (v.into_iter().rev().collect(), beta))
+}
+
+
+/**
+ * Inverse of the 3 x 3 upper triangular block t[k..k + 3][k..k + 3]
+ */
+fn upper_triangular_inverse_3(t: &Matrix<f64>, k: usize) -> [[f64; 3]; 3] {
+    let mut inv = [[0.; 3]; 3];
+
+    for j in 0..3 {
+        inv[j][j] = 1. / t[k + j][k + j];
+
+        for i in (0..j).rev() {
+            let sum: f64 = ((i + 1)..=j).map(|l| t[k + i][k + l] * inv[l][j]).sum();
+            inv[i][j] = -sum / t[k + i][k + i];
+        }
+    }
+
+    inv
+}
+
+
+/**
+ * Reads the eigenvalues of the pencil from the diagonal blocks of (S, T)
+ * For a 2 x 2 block: det(T) lambda² - ( s11 t22 + s22 t11 - s21 t12 ) lambda + det(S) = 0
+ */
+fn generalized_eigenvalues(s: &Matrix<f64>, t: &Matrix<f64>) -> Vec<(f64, f64)> {
+    let n = s.len();
+    let mut eigenvalues = Vec::with_capacity(n);
+    let mut k = 0;
+
+    while k < n {
+        if k + 1 < n && s[k + 1][k] != 0. {
+            let (s11, s12, s21, s22) = (s[k][k], s[k][k + 1], s[k + 1][k], s[k + 1][k + 1]);
+            let (t11, t12, t22) = (t[k][k], t[k][k + 1], t[k + 1][k + 1]);
+
+            let qa = t11 * t22;
+            let qb = -(s11 * t22 + s22 * t11 - s21 * t12);
+            let qc = s11 * s22 - s12 * s21;
+            let discriminant = qb * qb - 4. * qa * qc;
+
+            if discriminant < 0. {
+                let re = -qb / (2. * qa);
+                let im = f64::sqrt(-discriminant) / (2. * f64::abs(qa));
+                eigenvalues.push((re, im));
+                eigenvalues.push((re, -im));
+            } else {
+                // avoids the cancellation of the textbook formula
+                let root = -0.5 * (qb + f64::sqrt(discriminant).copysign(qb));
+                eigenvalues.push((root / qa, 0.));
+                eigenvalues.push((qc / root, 0.));
+            }
+
+            k += 2;
+        } else {
+            let lambda = if t[k][k] == 0. { f64::INFINITY } else { s[k][k] / t[k][k] };
+            eigenvalues.push((lambda, 0.));
+            k += 1;
+        }
+    }
+
+    eigenvalues
+}
+
+
+
+/**
+ * Right eigenvectors x = Z y of the pencil, with (beta S - alpha T) y = 0 solved by back-substitution
+ * on the quasi triangular pair, (alpha, beta) = (lambda, 1) for a finite eigenvalue and (1, 0) for an infinite one
+ * y is zero below the block of the eigenvalue, a null vector of the block on it, and the blocks above are solved
+ * from the bottom up in complex arithmetic, (re, im) pairs
+ * Pivots smaller than eps ( ||S|| + ||T|| ) are replaced by that value, as for repeated eigenvalues
+ * The eigenvectors are normalized to a unit Euclidean norm
+ */
+fn generalized_eigenvectors(s: &Matrix<f64>, t: &Matrix<f64>, z: &Matrix<f64>, eigenvalues: &[(f64, f64)]) -> Matrix<f64> {
+    let n = s.len();
+    let small = f64::max(f64::EPSILON * (infinity_norm(s) + infinity_norm(t)), f64::MIN_POSITIVE);
+    let mut eigenvectors = vec![vec![0.; n]; n];
+
+    // (start, size) of the diagonal blocks
+    let mut blocks = Vec::new();
+    let mut k = 0;
+
+    while k < n {
+        let size = if k + 1 < n && s[k + 1][k] != 0. { 2 } else { 1 };
+        blocks.push((k, size));
+        k += size;
+    }
+
+    for (block, &(start, size)) in blocks.iter().enumerate() {
+        for k in start..start + size {
+            let (re, im) = eigenvalues[k];
+
+            // the conjugate of the previous eigenvalue, already stored
+            if im < 0. {
+                continue;
+            }
+
+            let (alpha, beta) = if re.is_infinite() { ((1., 0.), 0.) } else { ((re, im), 1.) };
+            let m = |i: usize, j: usize| (beta * s[i][j] - alpha.0 * t[i][j], -alpha.1 * t[i][j]);
+            let mut y = vec![(0., 0.); n];
+
+            if size == 1 {
+                y[k] = (1., 0.);
+            } else {
+                let (m00, m01, m10, m11) = (m(start, start), m(start, start + 1), m(start + 1, start), m(start + 1, start + 1));
+
+                if complex_abs(m00) + complex_abs(m01) >= complex_abs(m10) + complex_abs(m11) && complex_abs(m00) + complex_abs(m01) > 0. {
+                    (y[start], y[start + 1]) = ((-m01.0, -m01.1), m00);
+                } else if complex_abs(m10) + complex_abs(m11) > 0. {
+                    (y[start], y[start + 1]) = (m11, (-m10.0, -m10.1));
+                } else {
+                    y[start] = (1., 0.);
+                }
+            }
+
+            for &(above, above_size) in blocks[..block].iter().rev() {
+                let rhs = |i: usize, y: &[(f64, f64)]| {
+                    (above + above_size..start + size).fold((0., 0.), |acc, j| {
+                        let product = complex_mul(m(i, j), y[j]);
+                        (acc.0 - product.0, acc.1 - product.1)
+                    })
+                };
+
+                if above_size == 1 {
+                    y[above] = complex_div(rhs(above, &y), pivot(m(above, above), small));
+                } else {
+                    let (m00, m01, m10, m11) = (m(above, above), m(above, above + 1), m(above + 1, above), m(above + 1, above + 1));
+                    let (r0, r1) = (rhs(above, &y), rhs(above + 1, &y));
+                    let (d0, d1) = (complex_mul(m00, m11), complex_mul(m01, m10));
+                    let det = pivot((d0.0 - d1.0, d0.1 - d1.1), small);
+
+                    let (u0, u1) = (complex_mul(r0, m11), complex_mul(m01, r1));
+                    let (v0, v1) = (complex_mul(m00, r1), complex_mul(m10, r0));
+                    y[above] = complex_div((u0.0 - u1.0, u0.1 - u1.1), det);
+                    y[above + 1] = complex_div((v0.0 - v1.0, v0.1 - v1.1), det);
+                }
+            }
+
+            let x: Vec<(f64, f64)> = z.iter()
+                .map(|row| row.iter().zip(&y).fold((0., 0.), |acc, (z_ij, y_j)| (acc.0 + z_ij * y_j.0, acc.1 + z_ij * y_j.1)))
+                .collect();
+            let norm = f64::sqrt(x.iter().map(|(a, b)| a * a + b * b).sum());
+
+            for (row, (a, b)) in eigenvectors.iter_mut().zip(&x) {
+                row[k] = a / norm;
+
+                if im > 0. {
+                    row[k + 1] = b / norm;
+                }
+            }
+        }
+    }
+
+    eigenvectors
+}
+
+
+fn pivot(p: (f64, f64), small: f64) -> (f64, f64) {
+    if complex_abs(p) < small { (small, 0.) } else { p }
+}
+
+
+fn complex_abs(a: (f64, f64)) -> f64 {
+    f64::hypot(a.0, a.1)
+}
+
+
+fn complex_mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+
+fn complex_div(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let d = b.0 * b.0 + b.1 * b.1;
+    ((a.0 * b.0 + a.1 * b.1) / d, (a.1 * b.0 - a.0 * b.1) / d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_algebra::exact_methods::inverse;
+
+    fn max_abs_difference(a: &Matrix<f64>, b: &Matrix<f64>) -> f64 {
+        a.iter()
+            .zip(b)
+            .flat_map(|(row_a, row_b)| row_a.iter().zip(row_b).map(|(x, y)| f64::abs(x - y)))
+            .fold(0., f64::max)
+    }
+
+    #[test]
+    fn faddeev_leverrier_matches_the_lu_determinant_and_inverse() {
+        let a = vec![
+            vec![4., -2., 1., 0.5],
+            vec![3., 6., -4., 1.],
+            vec![2., 1., 8., -3.],
+            vec![-1., 2., 0.5, 5.],
+        ];
+
+        let fl = faddeev_leverrier(&a);
+        let determinant = det(&a);
+
+        assert!(f64::abs(fl.determinant - determinant) <= 1e-10 * f64::abs(determinant));
+        assert!(max_abs_difference(&fl.inverse.expect("A is nonsingular"), &inverse(&a)) < 1e-12);
+
+        // adj(A) A = det(A) I
+        let identity_times_det: Matrix<f64> = get_identity_matrix(4).iter().map(|row| scalar_mul(row, determinant)).collect();
+        assert!(max_abs_difference(&matmat(&fl.adjugate, &a), &identity_times_det) < 1e-9);
+        assert!(max_abs_difference(&matmat(&a, &fl.adjugate), &identity_times_det) < 1e-9);
+    }
+
+    #[test]
+    fn faddeev_leverrier_of_a_singular_matrix_has_no_inverse() {
+        let a = vec![vec![1., 2., 3.], vec![4., 5., 6.], vec![7., 8., 9.]];
+        let fl = faddeev_leverrier(&a);
+
+        assert!(f64::abs(fl.determinant) < 1e-12);
+        assert!(fl.inverse.is_none());
+        assert!(max_abs_difference(&matmat(&fl.adjugate, &a), &vec![vec![0.; 3]; 3]) < 1e-12);
+    }
+
+    /**
+     * Largest component of A x - lambda B x over the eigenpairs, complex pairs split into real and imaginary parts
+     */
+    fn generalized_residual(a: &Matrix<f64>, b: &Matrix<f64>, qz: &GeneralizedSchur) -> f64 {
+        let n = a.len();
+        let column = |k: usize| qz.eigenvectors.iter().map(|row| row[k]).collect::<Vec<f64>>();
+        let mut residual: f64 = 0.;
+
+        assert!(qz.eigenvectors.iter().flatten().all(|x| x.is_finite()));
+
+        for (k, &(re, im)) in qz.eigenvalues.iter().enumerate() {
+            if im < 0. {
+                continue;
+            }
+
+            let xr = column(k);
+            let xi = if im > 0. { column(k + 1) } else { vec![0.; n] };
+            let (axr, axi, bxr, bxi) = (matvec(a, &xr), matvec(a, &xi), matvec(b, &xr), matvec(b, &xi));
+
+            for i in 0..n {
+                // B x = 0 for an infinite eigenvalue
+                let (real, imaginary) = if re.is_infinite() {
+                    (bxr[i], bxi[i])
+                } else {
+                    (axr[i] - re * bxr[i] + im * bxi[i], axi[i] - im * bxr[i] - re * bxi[i])
+                };
+
+                residual = residual.max(f64::abs(real)).max(f64::abs(imaginary));
+            }
+        }
+
+        residual
+    }
+
+    #[test]
+    fn qz_eigenvectors_satisfy_the_generalized_eigenproblem() {
+        let a = vec![
+            vec![1., 2., 3., 4., 5.],
+            vec![-2., 1., 0., 3., 1.],
+            vec![0.5, 4., -3., 1., 2.],
+            vec![3., -1., 2., 2., 0.],
+            vec![1., 0., 1., -4., 6.],
+        ];
+        let b = vec![
+            vec![2., 1., 0., 0., 1.],
+            vec![0., 3., 1., 0., 0.],
+            vec![1., 0., 4., 1., 0.],
+            vec![0., 1., 0., 2., 1.],
+            vec![0., 0., 1., 1., 5.],
+        ];
+
+        let qz = qz(&a, &b, &EigenOptions::default());
+
+        assert!(qz.converged);
+        assert!(qz.eigenvalues.iter().any(|(_, im)| *im != 0.));
+        assert!(generalized_residual(&a, &b, &qz) < 1e-10);
+    }
+
+    #[test]
+    fn qz_eigenvectors_of_infinite_eigenvalues_span_the_kernel_of_b() {
+        let a = vec![
+            vec![2., 1., 0., 1.],
+            vec![1., 3., 1., 0.],
+            vec![0., 1., 4., 1.],
+            vec![1., 0., 1., 5.],
+        ];
+        let b = vec![
+            vec![1., 0., 0., 0.],
+            vec![0., 1., 0., 0.],
+            vec![0., 0., 0., 0.],
+            vec![0., 0., 0., 1.],
+        ];
+
+        let qz = qz(&a, &b, &EigenOptions::default());
+
+        assert!(qz.converged);
+        assert_eq!(qz.eigenvalues.iter().filter(|(re, _)| re.is_infinite()).count(), 1);
+        assert!(generalized_residual(&a, &b, &qz) < 1e-10);
+    }
+
+    #[test]
+    fn francis_qr_gives_an_orthogonal_schur_decomposition() {
+        let a = vec![
+            vec![1., 2., 3., 4., 5.],
+            vec![-2., 1., 0., 3., 1.],
+            vec![0.5, 4., -3., 1., 2.],
+            vec![3., -1., 2., 2., 0.],
+            vec![1., 0., 1., -4., 6.],
+        ];
+
+        let schur = francis_qr(&a, true, &EigenOptions::default());
+        let z = schur.z.expect("the Schur vectors are accumulated");
+
+        assert!(schur.converged);
+        assert!(max_abs_difference(&matmat(&transpose(&z), &z), &get_identity_matrix(5)) < 1e-13);
+        assert!(max_abs_difference(&matmat(&matmat(&z, &schur.t), &transpose(&z)), &a) < 1e-12);
+
+        // quasi upper triangular, with 2 x 2 blocks only for complex pairs
+        for i in 1..5 {
+            for j in 0..i - 1 {
+                assert_eq!(schur.t[i][j], 0.);
+            }
+        }
+
+        let trace: f64 = schur.eigenvalues.iter().map(|(re, _)| re).sum();
+        assert!(f64::abs(trace - tr(&a)) < 1e-12);
+        assert!(schur.eigenvalues.iter().any(|(_, im)| *im != 0.));
+    }
+
+    #[test]
+    fn francis_qr_finds_the_roots_of_a_companion_matrix() {
+        // (x - 1)(x - 2)(x² + 1) = x⁴ - 3x³ + 3x² - 3x + 2
+        let a = vec![
+            vec![3., -3., 3., -2.],
+            vec![1., 0., 0., 0.],
+            vec![0., 1., 0., 0.],
+            vec![0., 0., 1., 0.],
+        ];
+
+        let options = EigenOptions { tolerance: f64::EPSILON, ..EigenOptions::default() };
+        let eigenvalues = francis_qr(&a, false, &options).eigenvalues;
+        let expected = [(0., -1.), (0., 1.), (1., 0.), (2., 0.)];
+
+        assert_eq!(eigenvalues.len(), 4);
+
+        for (expected_re, expected_im) in expected {
+            assert!(
+                eigenvalues.iter().any(|(re, im)| f64::abs(re - expected_re) < 1e-12 && f64::abs(im - expected_im) < 1e-12),
+                "{expected_re} + {expected_im}i not in {eigenvalues:?}"
+            );
+        }
+    }
+
+    /**
+     * Checks V^T V = I, V diag(eigenvalues) V^T = A and the increasing order
+     */
+    fn assert_symmetric_eigen(a: &Matrix<f64>, eigen: &SymmetricEigen, tolerance: f64) {
+        let n = a.len();
+        let v = &eigen.eigenvectors;
+        let v_lambda: Matrix<f64> = v.iter().map(|row| row.iter().zip(&eigen.eigenvalues).map(|(vij, lj)| vij * lj).collect()).collect();
+
+        assert!(eigen.converged);
+        assert!(eigen.eigenvalues.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(max_abs_difference(&matmat(&transpose(v), v), &get_identity_matrix(n)) < tolerance);
+        assert!(max_abs_difference(&matmat(&v_lambda, &transpose(v)), a) < tolerance * infinity_norm(a));
+    }
+
+    #[test]
+    fn symmetric_eigensolvers_diagonalize_with_orthogonal_vectors() {
+        let a = vec![
+            vec![4., 1., -2., 2., 0.],
+            vec![1., 2., 0., 1., 3.],
+            vec![-2., 0., 3., -2., 1.],
+            vec![2., 1., -2., -1., 0.5],
+            vec![0., 3., 1., 0.5, 5.],
+        ];
+        let options = EigenOptions::default();
+
+        let jacobi = jacobi_eigen(&a, &options);
+        let tridiagonal_ql = symmetric_eigen(&a, &options);
+
+        assert_symmetric_eigen(&a, &jacobi, 1e-12);
+        assert_symmetric_eigen(&a, &tridiagonal_ql, 1e-12);
+
+        for (x, y) in jacobi.eigenvalues.iter().zip(&tridiagonal_ql.eigenvalues) {
+            assert!(f64::abs(x - y) < 1e-10);
+        }
+    }
+
+    #[test]
+    fn symmetric_eigensolvers_handle_repeated_eigenvalues() {
+        // eigenvalues 1, 1 and 4 of J + I, J the matrix of ones
+        let a = vec![vec![2., 1., 1.], vec![1., 2., 1.], vec![1., 1., 2.]];
+        let options = EigenOptions::default();
+
+        for eigen in [jacobi_eigen(&a, &options), symmetric_eigen(&a, &options)] {
+            assert_symmetric_eigen(&a, &eigen, 1e-12);
+
+            for (lambda, expected) in eigen.eigenvalues.iter().zip([1., 1., 4.]) {
+                assert!(f64::abs(lambda - expected) < 1e-12);
+            }
+        }
+    }
+
+    /**
+     * Q diag(eigenvalues) Q^T with the Householder reflection Q = I - 2 u u^T / u^T u, u = (1, 2, 3, 4)
+     * Returns (A, Q), the columns of Q are the eigenvectors
+     */
+    fn known_symmetric_matrix(eigenvalues: &[f64]) -> (Matrix<f64>, Matrix<f64>) {
+        let u = [1., 2., 3., 4.];
+        let u_norm_2: f64 = u.iter().map(|x| x * x).sum();
+        let q: Matrix<f64> = (0..4)
+            .map(|i| (0..4).map(|j| if i == j { 1. } else { 0. } - 2. * u[i] * u[j] / u_norm_2).collect())
+            .collect();
+        let q_lambda: Matrix<f64> = q.iter().map(|row| row.iter().zip(eigenvalues).map(|(x, l)| x * l).collect()).collect();
+
+        (matmat(&q_lambda, &transpose(&q)), q)
+    }
+
+    /**
+     * sin of the angle between the unit vector v and the column k of Q
+     */
+    fn angle_to_column(v: &[f64], q: &Matrix<f64>, k: usize) -> f64 {
+        let cosine: f64 = v.iter().zip(q).map(|(x, row)| x * row[k]).sum();
+
+        // || v - (v^T q) q ||, without the cancellation of sqrt( 1 - cosine² )
+        f64::sqrt(v.iter().zip(q).map(|(x, row)| ( x - cosine * row[k] ).powi(2)).sum())
+    }
+
+    #[test]
+    fn power_and_inverse_iterations_find_the_extreme_and_shifted_eigenpairs() {
+        let (a, q) = known_symmetric_matrix(&[10., 4., 2., -1.]);
+        let options = EigenOptions::default();
+
+        let cases = [
+            (power_method(&a, &options), 10., 0),
+            (inverse_iteration(&a, &options), -1., 3),
+            (shifted_inverse_iteration(&a, 3.7, &options), 4., 1),
+            (shifted_inverse_iteration(&a, 2.2, &options), 2., 2),
+        ];
+
+        for ((lambda, v, iterations), expected, column) in cases {
+            assert!(iterations < options.max_iterations);
+            assert!(f64::abs(lambda - expected) < 1e-9, "{lambda} instead of {expected}");
+            assert!(eigenpair_residual(&matvec(&a, &v), lambda, &v) <= options.tolerance * f64::max(f64::abs(lambda), 1.));
+            assert!(angle_to_column(&v, &q, column) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn power_method_stops_at_the_iteration_limit_when_the_dominant_eigenvalue_is_not_unique() {
+        let (a, _) = known_symmetric_matrix(&[3., -3., 1., 0.5]);
+        let options = EigenOptions { max_iterations: 50, ..EigenOptions::default() };
+
+        let (_, _, iterations) = power_method(&a, &options);
+
+        assert_eq!(iterations, 50);
+    }
+
+    #[test]
+    fn rayleigh_quotient_iteration_converges_cubically() {
+        let (a, q) = known_symmetric_matrix(&[10., 4., 2., -1.]);
+
+        // 0.05 away from the eigenvector of 2
+        let x0: Vec<f64> = q.iter().map(|row| row[2] + 0.05 * ( row[0] - row[3] )).collect();
+        let mut angles = vec![angle_to_column(&normalize(&x0), &q, 2)];
+
+        for steps in 1..=2 {
+            let options = EigenOptions { tolerance: 0., max_iterations: steps };
+            let (_, v, _) = rayleigh_quotient_iteration(&a, Some(&x0), &options);
+            angles.push(angle_to_column(&v, &q, 2));
+        }
+
+        for pair in angles.windows(2) {
+            assert!(pair[1] <= 10. * pair[0].powi(3), "{angles:?}");
+        }
+
+        let (lambda, v, iterations) = rayleigh_quotient_iteration(&a, Some(&x0), &EigenOptions::default());
+        assert!(iterations <= 3);
+        assert!(f64::abs(lambda - 2.) < 1e-12);
+        assert!(angle_to_column(&v, &q, 2) < 1e-10);
+    }
+
+    #[test]
+    fn symmetric_generalized_eigenvectors_are_b_orthonormal() {
+        let a = vec![
+            vec![2., -1., 0., 3.],
+            vec![-1., 5., 1., 0.],
+            vec![0., 1., -3., 2.],
+            vec![3., 0., 2., 1.],
+        ];
+        let b = vec![
+            vec![4., 1., 0., 0.],
+            vec![1., 4., 1., 0.],
+            vec![0., 1., 4., 1.],
+            vec![0., 0., 1., 4.],
+        ];
+
+        let eigen = symmetric_generalized_eigen(&a, &b, &EigenOptions::default()).unwrap();
+        let x = &eigen.eigenvectors;
+        let bx = matmat(&b, x);
+        let bx_lambda: Matrix<f64> = bx.iter().map(|row| row.iter().zip(&eigen.eigenvalues).map(|(y, l)| y * l).collect()).collect();
+
+        assert!(eigen.converged);
+        assert!(eigen.eigenvalues.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(max_abs_difference(&matmat(&transpose(x), &bx), &get_identity_matrix(4)) < 1e-12);
+        // the deflation tolerance of 1e-10 bounds the residual, not the B-orthonormality
+        assert!(max_abs_difference(&matmat(&a, x), &bx_lambda) < 1e-9);
+
+        for lambda in &eigen.eigenvalues {
+            let pencil: Matrix<f64> = a.iter().zip(&b).map(|(ra, rb)| ra.iter().zip(rb).map(|(x, y)| x - lambda * y).collect()).collect();
+            assert!(f64::abs(det(&pencil)) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn symmetric_generalized_eigen_rejects_a_b_that_is_not_positive_definite() {
+        let a = vec![vec![1., 2.], vec![2., 3.]];
+        let options = EigenOptions::default();
+
+        let indefinite = vec![vec![1., 2.], vec![2., 1.]];
+        let singular = vec![vec![1., 1.], vec![1., 1.]];
+        let negative_definite = vec![vec![-2., 0.], vec![0., -1.]];
+
+        assert!(symmetric_generalized_eigen(&a, &indefinite, &options).is_none());
+        assert!(symmetric_generalized_eigen(&a, &singular, &options).is_none());
+        assert!(symmetric_generalized_eigen(&a, &negative_definite, &options).is_none());
+    }
+
+    #[test]
+    fn gershgorin_discs_have_the_off_diagonal_sums_as_radii() {
+        let a = vec![vec![4., 1., 0.], vec![-2., -3., 1.], vec![0.5, 0., 1.]];
+
+        let rows: Vec<(f64, f64)> = gershgorin_row_discs(&a).iter().map(|disc| (disc.center, disc.radius)).collect();
+        let columns: Vec<(f64, f64)> = gershgorin_column_discs(&a).iter().map(|disc| (disc.center, disc.radius)).collect();
+
+        assert_eq!(rows, vec![(4., 1.), (-3., 3.), (1., 0.5)]);
+        assert_eq!(columns, vec![(4., 2.5), (-3., 1.), (1., 1.)]);
+    }
+
+    #[test]
+    fn eigenvalues_lie_in_the_gershgorin_discs_and_the_spectral_radius_bounds() {
+        let a = vec![
+            vec![1., 2., 3., 4., 5.],
+            vec![-2., 1., 0., 3., 1.],
+            vec![0.5, 4., -3., 1., 2.],
+            vec![3., -1., 2., 2., 0.],
+            vec![1., 0., 1., -4., 6.],
+        ];
+
+        let eigenvalues = francis_qr(&a, false, &EigenOptions::default()).eigenvalues;
+        let row_discs = gershgorin_row_discs(&a);
+        let column_discs = gershgorin_column_discs(&a);
+        let regions = eigenvalue_inclusion_regions(&a);
+
+        for (re, im) in &eigenvalues {
+            assert!(row_discs.iter().any(|disc| disc.contains(*re, *im)));
+            assert!(column_discs.iter().any(|disc| disc.contains(*re, *im)));
+            assert!(regions.contains(*re, *im), "{re} + {im}i");
+        }
+
+        let rho = eigenvalues.iter().map(|(re, im)| f64::hypot(*re, *im)).fold(0., f64::max);
+        let (lower, upper) = spectral_radius_bounds(&a);
+
+        assert!(lower <= rho && rho <= upper, "{lower} <= {rho} <= {upper}");
+        assert_eq!(regions.spectral_radius_bounds, (lower, upper));
+
+        // a point far outside the discs
+        assert!(!regions.contains(0., 20.));
+    }
+
+    #[test]
+    fn spectral_radius_bounds_are_tight_for_a_diagonal_matrix() {
+        let a = vec![vec![3., 0.], vec![0., -3.]];
+        let regions = eigenvalue_inclusion_regions(&a);
+
+        assert_eq!(spectral_radius_bounds(&a), (3., 3.));
+        assert_eq!(regions.real_interval, (-3., 3.));
+        assert!(regions.contains(3., 0.) && regions.contains(-3., 0.));
+        assert!(!regions.contains(0., 0.));
+    }
+}
@@ -19,7 +19,7 @@ use super::helpers::{
  * this functions returns x, which is the variables vector, solution vector
  * starts with the first item and goes until the diagonal item
  */
-pub fn solve_inf(a: &Matrix<f64>, b: &Vec<f64>) -> Vec<f64>
+pub fn solve_inf(a: &Matrix<f64>, b: &[f64]) -> Vec<f64>
 {
     let n = b.len();
     let mut x = vec![0.0; n];
@@ -47,7 +47,7 @@ pub fn solve_inf(a: &Matrix<f64>, b: &Vec<f64>) -> Vec<f64>
  * this functions returns x, which is the variables vector, solution vector
  * starts with the diagonal item and goes until the last item
  */
-pub fn solve_sup(a: &Matrix<f64>, b: &Vec<f64>) -> Vec<f64>
+pub fn solve_sup(a: &Matrix<f64>, b: &[f64]) -> Vec<f64>
 {
     let n = b.len();
     let mut x = vec![0.0; n];
@@ -185,7 +185,7 @@ pub fn gaussian_compact(a: &mut Matrix<f64>, b: &mut Vec<f64>)
 }
 
 
-pub fn cholesky_solver(a: &Matrix<f64>, b: &Vec<f64>) -> Vec<f64>
+pub fn cholesky_solver(a: &Matrix<f64>, b: &[f64]) -> Vec<f64>
 {
     let (g, gt) = cholesky_method(a);
     let y = solve_inf(&g, b);
@@ -240,3 +240,462 @@ pub fn cond(a: &Matrix<f64>) -> f64 {
     let a_inverse = inverse(a);
     infinity_norm(a) * infinity_norm(&a_inverse)
 }
+
+
+/**
+ * Solves a tridiagonal system with the Thomas algorithm in O(n)
+ * sub[i] = a[i + 1][i], diag[i] = a[i][i], sup[i] = a[i][i + 1]
+ * sub and sup have n - 1 elements
+ * Returns None when a zero pivot appears, the algorithm does not pivot, or when the lengths do not match
+ */
+pub fn thomas_solver(sub: &[f64], diag: &[f64], sup: &[f64], d: &[f64]) -> Option<Vec<f64>>
+{
+    let n = diag.len();
+
+    if d.len() != n || sub.len() != n.saturating_sub(1) || sup.len() != n.saturating_sub(1) {
+        return None;
+    }
+
+    if n == 0 {
+        return Some(vec![]);
+    }
+
+    let mut c = vec![0.0; n]; // modified super diagonal
+    let mut x = vec![0.0; n];
+
+    if diag[0] == 0.0 {
+        return None;
+    }
+
+    c[0] = if n > 1 { sup[0] / diag[0] } else { 0.0 };
+    x[0] = d[0] / diag[0];
+
+    for i in 1..n {
+        let pivot = diag[i] - sub[i - 1] * c[i - 1];
+
+        if pivot == 0.0 {
+            return None;
+        }
+
+        c[i] = if i < n - 1 { sup[i] / pivot } else { 0.0 };
+        x[i] = (d[i] - sub[i - 1] * x[i - 1]) / pivot;
+    }
+
+    for i in (0..(n - 1)).rev() {
+        x[i] -= c[i] * x[i + 1];
+    }
+
+    Some(x)
+}
+
+
+/**
+ * Solves a cyclic tridiagonal system, which also has the corner entries
+ * a[0][n - 1] = sub[0] and a[n - 1][0] = sup[n - 1]
+ * sub, diag and sup have n elements, sub[i] = a[i][i - 1] and sup[i] = a[i][i + 1] otherwise
+ * Uses the Sherman-Morrison formula on top of two Thomas solutions, n must be at least 3
+ * Returns None when a Thomas solution fails, the Sherman-Morrison denominator is zero or the lengths do not match
+ */
+pub fn cyclic_thomas_solver(sub: &[f64], diag: &[f64], sup: &[f64], d: &[f64]) -> Option<Vec<f64>>
+{
+    let n = diag.len();
+
+    if n < 3 || sub.len() != n || sup.len() != n || d.len() != n {
+        return None;
+    }
+
+    let beta = sub[0]; // a[0][n - 1]
+    let alpha = sup[n - 1]; // a[n - 1][0]
+    // any nonzero gamma works, -diag[0] keeps t_diag[0] = 2 diag[0] away from zero when it can
+    let gamma = if diag[0] != 0.0 { -diag[0] } else { 1.0 };
+
+    // T = A - u v^T, u = [gamma, 0, ..., 0, alpha], v = [1, 0, ..., 0, beta / gamma]
+    let mut t_diag = diag.to_vec();
+    t_diag[0] -= gamma;
+    t_diag[n - 1] -= alpha * beta / gamma;
+
+    let mut u = vec![0.0; n];
+    u[0] = gamma;
+    u[n - 1] = alpha;
+
+    let y = thomas_solver(&sub[1..], &t_diag, &sup[..(n - 1)], d)?;
+    let z = thomas_solver(&sub[1..], &t_diag, &sup[..(n - 1)], &u)?;
+
+    let v_y = y[0] + beta / gamma * y[n - 1];
+    let v_z = z[0] + beta / gamma * z[n - 1];
+    let denominator = 1.0 + v_z;
+
+    if denominator == 0.0 {
+        return None;
+    }
+
+    let factor = v_y / denominator;
+
+    Some(y.iter().zip(&z).map(|(yi, zi)| yi - factor * zi).collect())
+}
+
+
+/**
+ * Band matrix n x n with kl sub diagonals and ku super diagonals
+ * Only the band is stored: a[i][j] = data[i][j + kl - i], for i - kl <= j <= i + ku
+ */
+#[derive(Clone)]
+pub struct BandMatrix {
+    pub n: usize,
+    pub kl: usize,
+    pub ku: usize,
+    pub data: Matrix<f64>,
+}
+
+impl BandMatrix {
+    pub fn new(n: usize, kl: usize, ku: usize) -> Self {
+        Self { n, kl, ku, data: vec![vec![0.0; kl + ku + 1]; n] }
+    }
+
+    /**
+     * Copies the band of a dense matrix, the entries outside it are ignored
+     */
+    pub fn from_dense(a: &Matrix<f64>, kl: usize, ku: usize) -> Self {
+        let n = a.len();
+        let mut band = Self::new(n, kl, ku);
+
+        for (i, row) in a.iter().enumerate() {
+            let first = i.saturating_sub(kl);
+            let last = usize::min(n, i + ku + 1);
+
+            for (j, el) in row.iter().enumerate().take(last).skip(first) {
+                band.set(i, j, *el);
+            }
+        }
+
+        band
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        if j + self.kl < i || j > i + self.ku {
+            0.0
+        } else {
+            self.data[i][j + self.kl - i]
+        }
+    }
+
+    /**
+     * Panics when (i, j) is outside the band
+     */
+    pub fn set(&mut self, i: usize, j: usize, value: f64) {
+        assert!(j + self.kl >= i && j <= i + self.ku, "entry ({}, {}) is outside the band", i, j);
+        self.data[i][j + self.kl - i] = value;
+    }
+}
+
+
+/**
+ * LU decomposition of a band matrix without pivoting, which keeps the bandwidths
+ * Returns a band matrix holding L (unit diagonal not stored, kl sub diagonals) and U (ku super diagonals),
+ * or None when a zero pivot appears or data is not n rows of kl + ku + 1 elements
+ * O(n * kl * ku) operations
+ */
+pub fn banded_lu_decomp(a: &BandMatrix) -> Option<BandMatrix>
+{
+    let n = a.n;
+
+    if a.data.len() != n || a.data.iter().any(|row| row.len() != a.kl + a.ku + 1) {
+        return None;
+    }
+    let mut lu = a.clone();
+
+    for k in 0..n {
+        let pivot = lu.get(k, k);
+
+        if pivot == 0.0 {
+            return None;
+        }
+
+        for i in (k + 1)..usize::min(n, k + a.kl + 1) {
+            let p = lu.get(i, k) / pivot;
+            lu.set(i, k, p);
+
+            for j in (k + 1)..usize::min(n, k + a.ku + 1) {
+                let value = lu.get(i, j) - p * lu.get(k, j);
+                lu.set(i, j, value);
+            }
+        }
+    }
+
+    Some(lu)
+}
+
+
+/**
+ * Solves a band linear system with the banded LU decomposition
+ * Ly = b
+ * Ux = y
+ * Returns None when the decomposition fails or b does not have n elements
+ */
+pub fn banded_lu_solver(a: &BandMatrix, b: &[f64]) -> Option<Vec<f64>>
+{
+    let n = a.n;
+
+    if b.len() != n {
+        return None;
+    }
+
+    let lu = banded_lu_decomp(a)?;
+    let mut x = b.to_vec();
+
+    for i in 0..n {
+        for j in i.saturating_sub(a.kl)..i {
+            x[i] -= lu.get(i, j) * x[j];
+        }
+    }
+
+    for i in (0..n).rev() {
+        for j in (i + 1)..usize::min(n, i + a.ku + 1) {
+            x[i] -= lu.get(i, j) * x[j];
+        }
+
+        x[i] /= lu.get(i, i);
+    }
+
+    Some(x)
+}
+
+
+/**
+ * LU decomposition with partial pivoting, PA = LU, kept to solve many systems with the same matrix
+ * L (unit diagonal not stored) and U share the matrix lu, perm[i] is the row of A moved to row i
+ */
+pub struct LuFactorization {
+    lu: Matrix<f64>,
+    perm: Vec<usize>,
+    swaps: usize,
+}
+
+impl LuFactorization {
+    /**
+     * Returns None when the matrix is singular, that is, when a whole pivot column is zero
+     */
+    pub fn new(a: &Matrix<f64>) -> Option<Self> {
+        let n = a.len();
+        let mut lu = a.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut swaps = 0;
+
+        for k in 0..n {
+            let mut max_index = k;
+
+            for i in (k + 1)..n {
+                if f64::abs(lu[i][k]) > f64::abs(lu[max_index][k]) {
+                    max_index = i;
+                }
+            }
+
+            if lu[max_index][k] == 0.0 {
+                return None;
+            }
+
+            if max_index != k {
+                lu.swap(k, max_index);
+                perm.swap(k, max_index);
+                swaps += 1;
+            }
+
+            let (top, bottom) = lu.split_at_mut(k + 1);
+            let pivot_row = &top[k];
+
+            for row in bottom.iter_mut() {
+                let p = row[k] / pivot_row[k];
+                row[k] = p;
+
+                for (el, pivot_el) in row[(k + 1)..].iter_mut().zip(&pivot_row[(k + 1)..]) {
+                    *el -= p * pivot_el;
+                }
+            }
+        }
+
+        Some(Self { lu, perm, swaps })
+    }
+
+    /**
+     * Solves Ax = b
+     * Ly = Pb
+     * Ux = y
+     */
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.lu.len();
+        let mut x: Vec<f64> = self.perm.iter().map(|i| b[*i]).collect();
+
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.lu[i][j] * x[j];
+            }
+        }
+
+        for i in (0..n).rev() {
+            for j in (i + 1)..n {
+                x[i] -= self.lu[i][j] * x[j];
+            }
+
+            x[i] /= self.lu[i][i];
+        }
+
+        x
+    }
+
+    /**
+     * det(A) = (-1)^swaps * prod(u[i][i])
+     */
+    pub fn det(&self) -> f64 {
+        let sign = if self.swaps.is_multiple_of(2) { 1.0 } else { -1.0 };
+        sign * (0..self.lu.len()).map(|i| self.lu[i][i]).product::<f64>()
+    }
+}
+
+
+/**
+ * Returns the determinant of a square matrix from its LU decomposition with partial pivoting
+ */
+pub fn det(a: &Matrix<f64>) -> f64 {
+    LuFactorization::new(a).map_or(0.0, |lu| lu.det())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Dense form of the cyclic tridiagonal matrix of cyclic_thomas_solver
+     */
+    fn cyclic_matrix(sub: &[f64], diag: &[f64], sup: &[f64]) -> Matrix<f64> {
+        let n = diag.len();
+        let mut a = vec![vec![0.0; n]; n];
+
+        for i in 0..n {
+            a[i][i] = diag[i];
+            a[i][(i + n - 1) % n] = sub[i];
+            a[i][(i + 1) % n] = sup[i];
+        }
+
+        a
+    }
+
+    fn assert_solves(sub: &[f64], diag: &[f64], sup: &[f64], d: &[f64]) {
+        let x = cyclic_thomas_solver(sub, diag, sup, d).expect("the system is nonsingular");
+        let a = cyclic_matrix(sub, diag, sup);
+
+        for (row, di) in a.iter().zip(d) {
+            let ax: f64 = row.iter().zip(&x).map(|(aij, xj)| aij * xj).sum();
+            assert!(f64::abs(ax - di) < 1e-12, "A x = {ax}, d = {di}");
+        }
+    }
+
+    #[test]
+    fn cyclic_thomas_solver_solves_diagonally_dominant_systems() {
+        assert_solves(&[1.0, -1.0, 2.0, 0.5, 1.0], &[4.0, 5.0, 6.0, 4.0, 3.0], &[1.0, 2.0, -1.0, 1.0, 0.5], &[1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn cyclic_thomas_solver_handles_a_zero_first_diagonal_element() {
+        assert_solves(&[1.0, 1.0, 1.0, 1.0], &[0.0, 4.0, 4.0, 4.0], &[2.0, 1.0, 1.0, 1.0], &[1.0, -2.0, 3.0, 0.5]);
+    }
+
+    fn tridiagonal_matrix(sub: &[f64], diag: &[f64], sup: &[f64]) -> Matrix<f64> {
+        let n = diag.len();
+        let mut a = vec![vec![0.0; n]; n];
+
+        for i in 0..n {
+            a[i][i] = diag[i];
+
+            if i + 1 < n {
+                a[i + 1][i] = sub[i];
+                a[i][i + 1] = sup[i];
+            }
+        }
+
+        a
+    }
+
+    fn max_difference(x: &[f64], y: &[f64]) -> f64 {
+        x.iter().zip(y).map(|(xi, yi)| f64::abs(xi - yi)).fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn thomas_solver_matches_the_dense_lu_solver() {
+        let sub = [1.0, -2.0, 0.5, 3.0, -1.0];
+        let diag = [4.0, 6.0, -5.0, 7.0, 8.0, 3.0];
+        let sup = [2.0, 1.0, -1.0, 2.0, 0.5];
+        let d = vec![1.0, -2.0, 3.0, 0.5, 4.0, -1.0];
+
+        let x = thomas_solver(&sub, &diag, &sup, &d).expect("the system is diagonally dominant");
+
+        assert!(max_difference(&x, &lu_solver(&tridiagonal_matrix(&sub, &diag, &sup), &d)) < 1e-13);
+        assert_eq!(thomas_solver(&[], &[2.0], &[], &[3.0]), Some(vec![1.5]));
+        assert_eq!(thomas_solver(&[], &[], &[], &[]), Some(vec![]));
+    }
+
+    #[test]
+    fn thomas_solvers_reject_mismatched_lengths() {
+        let diag = [4.0, 4.0, 4.0];
+
+        assert_eq!(thomas_solver(&[1.0, 1.0], &diag, &[1.0, 1.0], &[1.0, 1.0]), None);
+        assert_eq!(thomas_solver(&[1.0], &diag, &[1.0, 1.0], &[1.0, 1.0, 1.0]), None);
+        assert_eq!(thomas_solver(&[1.0, 1.0], &diag, &[1.0, 1.0, 1.0], &[1.0, 1.0, 1.0]), None);
+        assert_eq!(thomas_solver(&[], &[], &[], &[1.0]), None);
+        assert_eq!(thomas_solver(&[1.0, 1.0], &[0.0, 4.0, 4.0], &[1.0, 1.0], &[1.0, 1.0, 1.0]), None);
+
+        assert_eq!(cyclic_thomas_solver(&[1.0, 1.0], &diag, &[1.0, 1.0, 1.0], &[1.0, 1.0, 1.0]), None);
+        assert_eq!(cyclic_thomas_solver(&[1.0, 1.0, 1.0], &diag, &[1.0, 1.0, 1.0], &[1.0, 1.0]), None);
+    }
+
+    #[test]
+    fn banded_lu_solver_matches_the_dense_lu_solver_on_a_pentadiagonal_system() {
+        let n = 8;
+        let a: Matrix<f64> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| match i as i64 - j as i64 {
+                        0 => 6.0 + i as f64,
+                        -1 | 1 => -1.5 + 0.25 * j as f64,
+                        -2 | 2 => 0.5 - 0.1 * i as f64,
+                        _ => 0.0,
+                    })
+                    .collect()
+            })
+            .collect();
+        let b: Vec<f64> = (0..n).map(|i| f64::sin(i as f64 + 1.0)).collect();
+
+        let band = BandMatrix::from_dense(&a, 2, 2);
+
+        for (i, row) in a.iter().enumerate() {
+            for (j, el) in row.iter().enumerate() {
+                assert_eq!(band.get(i, j), *el);
+            }
+        }
+
+        let x = banded_lu_solver(&band, &b).expect("the system is diagonally dominant");
+        assert!(max_difference(&x, &lu_solver(&a, &b)) < 1e-13);
+
+        // the lower and upper bandwidths may differ
+        let lower: Matrix<f64> = a.iter().enumerate().map(|(i, row)| row.iter().enumerate().map(|(j, el)| if j > i + 1 { 0.0 } else { *el }).collect()).collect();
+        let x = banded_lu_solver(&BandMatrix::from_dense(&lower, 2, 1), &b).expect("the system is diagonally dominant");
+        assert!(max_difference(&x, &lu_solver(&lower, &b)) < 1e-13);
+    }
+
+    #[test]
+    fn banded_lu_rejects_mismatched_lengths() {
+        let mut band = BandMatrix::from_dense(&vec![vec![2.0, 1.0], vec![1.0, 2.0]], 1, 1);
+
+        assert_eq!(banded_lu_solver(&band, &[3.0, 3.0]), Some(vec![1.0, 1.0]));
+        assert_eq!(banded_lu_solver(&band, &[1.0]), None);
+        assert_eq!(banded_lu_solver(&band, &[1.0, 1.0, 1.0]), None);
+
+        band.n = 3;
+        assert!(banded_lu_decomp(&band).is_none());
+
+        band.n = 2;
+        band.data[1].pop();
+        assert!(banded_lu_decomp(&band).is_none());
+        assert_eq!(banded_lu_solver(&band, &[1.0, 1.0]), None);
+    }
+}
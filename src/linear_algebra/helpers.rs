@@ -2,14 +2,15 @@ use crate::types::Matrix;
 
 /**
  * Returns the vector that results from the product between
- * a matrix n x n and a vector of dimension n
+ * a matrix m x n and a vector of dimension n
  */
 pub fn matvec(a: &Matrix<f64>, v: &Vec<f64>) -> Vec<f64>
 {
-    let mut x: Vec<f64> = vec![0.0; v.len()];
+    let m = a.len();
     let n = v.len();
+    let mut x: Vec<f64> = vec![0.0; m];
 
-    for i in 0..n {
+    for i in 0..m {
         let mut sum = 0.0;
 
         for j in 0..n {
@@ -24,19 +25,21 @@ pub fn matvec(a: &Matrix<f64>, v: &Vec<f64>) -> Vec<f64>
 
 
 /**
- * Return the product of two matrices
+ * Return the product of two matrices, a (m x p) and b (p x n)
  */
 pub fn matmat(a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64>
 {
-    let n = a.len();
-    let mut c = vec![vec![0.0; n]; n];
+    let m = a.len();
+    let p = b.len();
+    let n = b[0].len();
+    let mut c = vec![vec![0.0; n]; m];
 
-    for i in 0..n {
+    for i in 0..m {
         
         for j in 0..n {
             let mut sum = 0.0;
             
-            for k in 0..n {
+            for k in 0..p {
                 sum += a[i][k] * b[k][j];
             }
 
@@ -94,11 +97,19 @@ pub fn subvec(u: &Vec<f64>, v: &Vec<f64>) -> Vec<f64>
 }
 
 
+/**
+ * Returns the dot product of two vectors
+ */
+pub fn dot(u: &[f64], v: &[f64]) -> f64 {
+    u.iter().zip(v).map(|(ui, vi)| ui * vi).sum()
+}
+
+
 /**
  * Returns the Euclidean norm of a vector
  */
-pub fn vecnorm(v: &Vec<f64>) -> f64 {
-    let norm: f64 = v.into_iter().map(|component| component * component).sum();
+pub fn vecnorm(v: &[f64]) -> f64 {
+    let norm: f64 = v.iter().map(|component| component * component).sum();
     f64::sqrt(norm)
 }
 
@@ -1,64 +1,898 @@
 use crate::types::Matrix;
-use super::helpers::{lrb_star_decomp, matsum, matvec, subvec, vecnorm};
+use super::eigen::{power_method, EigenOptions};
+use super::preconditioners::Preconditioner;
+use super::helpers::{dot, subvec, vecnorm};
 
 
-pub fn jacobi_richardson_solver(a: &Matrix<f64>, b: &Vec<f64>) -> Vec<f64> {
-    let n = a.len();
-    let epsilon = 1e-17;
+/**
+ * Defines when an iterative solver stops
+ *  - Residual: ||b - Ax[k]|| / ||b|| < tolerance
+ *  - Step: ||x[k] - x[k-1]|| / ||x[k]|| < tolerance
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum StoppingCriterion {
+    Residual,
+    Step,
+}
+
 
-    let (l_star, r_star, b_star) = lrb_star_decomp(a, b);
-    let lr = matsum(&l_star, &r_star);
+/**
+ * Options shared by the iterative solvers
+ * initial_guess defaults to the zero vector
+ */
+pub struct IterativeOptions {
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    pub initial_guess: Option<Vec<f64>>,
+    pub criterion: StoppingCriterion,
+}
 
-    let mut x0 = vec![0.0; n];
-    let mut x1 = vec![0.0; n];
+impl Default for IterativeOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-10,
+            max_iterations: 1000,
+            initial_guess: None,
+            criterion: StoppingCriterion::Residual,
+        }
+    }
+}
 
-    loop {
-        x1 = subvec(&b_star, &matvec(&lr, &x0));
 
-        if vecnorm(&subvec(&x1, &x0)) / vecnorm(&x1) < epsilon {
-            break;
+/**
+ * Result of an iterative solver
+ * convergence_guaranteed tells whether a sufficient convergence criterion holds for the matrix
+ * residual_history[k] = ||b - Ax[k]||, where x[0] is the initial guess
+ */
+pub struct IterativeReport {
+    pub solution: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+    pub convergence_guaranteed: bool,
+    pub residual_history: Vec<f64>,
+}
+
+
+/**
+ * Row criterion: max, 1 <= i <= n ( sum(|a[i][j]|, j != i) / |a[i][i]| ) < 1
+ * Sufficient for the convergence of the Jacobi and Gauss-Seidel methods
+ */
+pub fn row_criterion<A: RowMatrix + ?Sized>(a: &A) -> bool {
+    (0..a.dim()).all(|i| {
+        let (diagonal, sum) = split_row(a, i, |_, el| f64::abs(el));
+        sum < f64::abs(diagonal)
+    })
+}
+
+
+/**
+ * Column criterion: max, 1 <= j <= n ( sum(|a[i][j]|, i != j) / |a[j][j]| ) < 1
+ * Sufficient for the convergence of the Jacobi method
+ */
+pub fn column_criterion<A: RowMatrix + ?Sized>(a: &A) -> bool {
+    let n = a.dim();
+    let mut sums = vec![0.0; n];
+
+    for i in 0..n {
+        for (j, el) in a.row_entries(i) {
+            if i != j {
+                sums[j] += f64::abs(el);
+            }
         }
+    }
 
-        x0 = x1.clone();
+    a.diagonal().iter().zip(&sums).all(|(d, sum)| *sum < f64::abs(*d))
+}
+
+
+/**
+ * Sassenfeld criterion:
+ * beta[i] = ( sum(|a[i][j]| * beta[j], j < i) + sum(|a[i][j]|, j > i) ) / |a[i][i]|
+ * max beta[i] < 1 is sufficient for the convergence of the Gauss-Seidel method
+ */
+pub fn sassenfeld_criterion<A: RowMatrix + ?Sized>(a: &A) -> bool {
+    let n = a.dim();
+    // beta[j] = 1 for the rows not visited yet, so the same sum covers j < i and j > i
+    let mut beta = vec![1.0; n];
+
+    for i in 0..n {
+        let (diagonal, sum) = split_row(a, i, |j, el| f64::abs(el) * beta[j]);
+        beta[i] = sum / f64::abs(diagonal);
+
+        if beta[i] >= 1.0 {
+            return false;
+        }
     }
 
-    x1
+    true
 }
 
 
-pub fn gauss_seidel_solver(a: &Matrix<f64>, b: &Vec<f64>) -> Vec<f64> {
-    let n = a.len();
-    let epsilon = 1e-17;
+/**
+ * x1[i] = ( b[i] - sum(a[i][j] * x0[j], j != i) ) / a[i][i]
+ */
+pub fn jacobi_richardson_solver<A: RowMatrix + ?Sized>(a: &A, b: &[f64], options: &IterativeOptions) -> IterativeReport {
+    let n = a.dim();
+    let convergence_guaranteed = row_criterion(a) || column_criterion(a);
+
+    iterate(a, b, options, convergence_guaranteed, |x0| {
+        (0..n).map(|i| {
+            let (diagonal, sum) = split_row(a, i, |j, el| el * x0[j]);
+            (b[i] - sum) / diagonal
+        }).collect()
+    })
+}
+
 
-    let (l_star, r_star, b_star) = lrb_star_decomp(a, b);
+/**
+ * x1[i] = ( b[i] - sum(a[i][j] * x1[j], j < i) - sum(a[i][j] * x0[j], j > i) ) / a[i][i]
+ */
+pub fn gauss_seidel_solver<A: RowMatrix + ?Sized>(a: &A, b: &[f64], options: &IterativeOptions) -> IterativeReport {
+    let n = a.dim();
+    let convergence_guaranteed = row_criterion(a) || sassenfeld_criterion(a);
 
-    let mut x0 = vec![0.0; n];
-    let mut x1 = vec![0.0; n];
+    iterate(a, b, options, convergence_guaranteed, |x0| {
+        let mut x1 = x0.clone();
 
-    loop {
         for i in 0..n {
-            let mut r = 0.0;
+            // x1 already holds the new values for j < i and the old ones for j > i
+            let (diagonal, sum) = split_row(a, i, |j, el| el * x1[j]);
+            x1[i] = (b[i] - sum) / diagonal;
+        }
+
+        x1
+    })
+}
+
+
+/**
+ * Returns a[i][i] and sum(term(j, a[i][j]), j != i)
+ */
+fn split_row<A, F>(a: &A, i: usize, mut term: F) -> (f64, f64)
+where
+    A: RowMatrix + ?Sized,
+    F: FnMut(usize, f64) -> f64,
+{
+    let mut diagonal = 0.0;
+    let mut sum = 0.0;
+
+    for (j, el) in a.row_entries(i) {
+        if i == j {
+            diagonal = el;
+        } else {
+            sum += term(j, el);
+        }
+    }
+
+    (diagonal, sum)
+}
+
+
+/**
+ * Successive over-relaxation
+ * x[i] = (1 - omega) * x[i] + omega * ( b[i] - sum(a[i][j] * x[j], j != i) ) / a[i][i], visiting the rows in order
+ * omega = 1 is the Gauss-Seidel method, omega = None estimates the optimal relaxation factor
+ */
+pub fn sor_solver<A: RowMatrix + ?Sized>(a: &A, b: &[f64], omega: Option<f64>, options: &IterativeOptions) -> IterativeReport {
+    let n = a.dim();
+    let omega = omega.unwrap_or_else(|| optimal_relaxation_factor(a));
+    let convergence_guaranteed = 0.0 < omega && omega < 2.0 && is_symmetric_diagonally_dominant(a);
+    let order: Vec<usize> = (0..n).collect();
+
+    iterate(a, b, options, convergence_guaranteed, |x0| {
+        let mut x1 = x0.clone();
+        sor_sweep(a, b, &mut x1, omega, &order);
+        x1
+    })
+}
+
+
+/**
+ * Symmetric successive over-relaxation
+ * Each iteration is a forward SOR sweep followed by a backward SOR sweep
+ * omega = None estimates the optimal relaxation factor
+ */
+pub fn ssor_solver<A: RowMatrix + ?Sized>(a: &A, b: &[f64], omega: Option<f64>, options: &IterativeOptions) -> IterativeReport {
+    let n = a.dim();
+    let omega = omega.unwrap_or_else(|| optimal_relaxation_factor(a));
+    let convergence_guaranteed = 0.0 < omega && omega < 2.0 && is_symmetric_diagonally_dominant(a);
+    let forward: Vec<usize> = (0..n).collect();
+    let backward: Vec<usize> = (0..n).rev().collect();
+
+    iterate(a, b, options, convergence_guaranteed, |x0| {
+        let mut x1 = x0.clone();
+        sor_sweep(a, b, &mut x1, omega, &forward);
+        sor_sweep(a, b, &mut x1, omega, &backward);
+        x1
+    })
+}
+
+
+/**
+ * Estimates the optimal relaxation factor of the SOR method
+ * omega = 2 / ( 1 + sqrt(1 - rho²) )
+ * rho is the spectral radius of the Jacobi iteration matrix B = -D^-1 (A - D), computed with the power method
+ * applied to B², since the dominant eigenvalues of B usually come in pairs +-rho
+ * B is applied row by row and never formed
+ * Returns 1 (Gauss-Seidel) when rho >= 1 or when the power method reaches its iteration limit
+ */
+pub fn optimal_relaxation_factor<A: RowMatrix + ?Sized>(a: &A) -> f64 {
+    let options = EigenOptions::default();
+    let (lambda, _, iterations) = power_method(&JacobiSquared { a }, &options);
+    let rho_2 = f64::abs(lambda);
+
+    if iterations >= options.max_iterations || rho_2 >= 1.0 {
+        return 1.0;
+    }
+
+    2.0 / (1.0 + f64::sqrt(1.0 - rho_2))
+}
+
+
+/**
+ * Square B² of the Jacobi iteration matrix B = -D^-1 (A - D), as an operator
+ */
+struct JacobiSquared<'a, A: RowMatrix + ?Sized> {
+    a: &'a A,
+}
+
+impl<A: RowMatrix + ?Sized> JacobiSquared<'_, A> {
+    fn jacobi(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.a.dim())
+            .map(|i| {
+                let (diagonal, sum) = split_row(self.a, i, |j, el| el * x[j]);
+                -sum / diagonal
+            })
+            .collect()
+    }
+}
+
+impl<A: RowMatrix + ?Sized> LinearOperator for JacobiSquared<'_, A> {
+    fn dim(&self) -> usize {
+        self.a.dim()
+    }
+
+    fn apply(&self, x: &[f64]) -> Vec<f64> {
+        self.jacobi(&self.jacobi(x))
+    }
+}
+
+
+/**
+ * Updates x in place visiting the rows in the given order
+ */
+fn sor_sweep<A: RowMatrix + ?Sized>(a: &A, b: &[f64], x: &mut [f64], omega: f64, order: &[usize]) {
+    for &i in order {
+        let (diagonal, sum) = split_row(a, i, |j, el| el * x[j]);
+        x[i] = (1.0 - omega) * x[i] + omega * (b[i] - sum) / diagonal;
+    }
+}
+
+
+/**
+ * Sufficient condition for a symmetric positive definite matrix, checked on the stored entries only:
+ * a is symmetric, has a positive diagonal and is irreducibly diagonally dominant, i.e. its graph is connected,
+ * |a[i][i]| >= sum(|a[i][j]|, j != i) in every row, with strict inequality in at least one
+ * (Gershgorin's theorem gives A >= 0 and Taussky's theorem that A is nonsingular)
+ */
+fn is_symmetric_diagonally_dominant<A: RowMatrix + ?Sized>(a: &A) -> bool {
+    let n = a.dim();
+    let mut entries: Vec<(usize, usize, f64)> = Vec::new();
+    let mut strict = false;
+
+    for i in 0..n {
+        let (diagonal, sum) = split_row(a, i, |_, el| f64::abs(el));
+
+        if diagonal <= 0.0 || sum > diagonal {
+            return false;
+        }
+
+        strict |= sum < diagonal;
+        entries.extend(a.row_entries(i).filter(|(j, el)| *j != i && *el != 0.0).map(|(j, el)| (i, j, el)));
+    }
 
-            // lower triangular matrix i > j
-            for j in 0..i {
-                r -= l_star[i][j] * x1[j];
+    let mut transposed: Vec<(usize, usize, f64)> = entries.iter().map(|(i, j, el)| (*j, *i, *el)).collect();
+    transposed.sort_by_key(|(i, j, _)| (*i, *j));
+    entries.sort_by_key(|(i, j, _)| (*i, *j));
+
+    let symmetric = entries.iter().zip(&transposed).all(|((i, j, x), (k, l, y))| {
+        i == k && j == l && f64::abs(x - y) <= 1e-12 * f64::max(f64::abs(*x), 1.0)
+    });
+
+    if !strict || !symmetric {
+        return false;
+    }
+
+    // the entries are sorted by row, so the neighbours of i are a contiguous range
+    let mut visited = vec![false; n];
+    let mut stack = vec![0];
+    visited[0] = true;
+
+    while let Some(i) = stack.pop() {
+        let start = entries.partition_point(|(k, _, _)| *k < i);
+
+        for (_, j, _) in entries[start..].iter().take_while(|(k, _, _)| *k == i) {
+            if !visited[*j] {
+                visited[*j] = true;
+                stack.push(*j);
             }
+        }
+    }
+
+    visited.into_iter().all(|v| v)
+}
+
+
+/**
+ * Runs x[k+1] = sweep(x[k]) from the initial guess until the stopping criterion
+ * is satisfied or the iteration limit is reached
+ */
+pub(crate) fn iterate<A, F>(a: &A, b: &[f64], options: &IterativeOptions, convergence_guaranteed: bool, mut sweep: F) -> IterativeReport
+where
+    A: LinearOperator + ?Sized,
+    F: FnMut(&Vec<f64>) -> Vec<f64>,
+{
+    let n = a.dim();
+    let b_norm = f64::max(vecnorm(b), f64::MIN_POSITIVE);
+
+    let mut x0 = options.initial_guess.clone().unwrap_or_else(|| vec![0.0; n]);
+    let mut residual_history = vec![vecnorm(&residual(a, b, &x0))];
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < options.max_iterations {
+        let x1 = sweep(&x0);
+        iterations += 1;
+
+        let residual = vecnorm(&residual(a, b, &x1));
+        residual_history.push(residual);
+
+        let error = match options.criterion {
+            StoppingCriterion::Residual => residual / b_norm,
+            StoppingCriterion::Step => vecnorm(&subvec(&x1, &x0)) / f64::max(vecnorm(&x1), f64::MIN_POSITIVE),
+        };
+
+        x0 = x1;
+
+        if error < options.tolerance {
+            converged = true;
+            break;
+        }
+
+        if !residual.is_finite() {
+            break;
+        }
+    }
 
-            // upper triangular matrix i < j
-            for j in (i + 1)..n {
-                r -= r_star[i][j] * x0[j];
+    IterativeReport {
+        solution: x0,
+        iterations,
+        converged,
+        convergence_guaranteed,
+        residual_history,
+    }
+}
+
+
+/**
+ * A square linear operator that only knows how to multiply itself by a vector
+ * This is all the Krylov solvers need from the matrix of the system
+ */
+pub trait LinearOperator {
+    fn dim(&self) -> usize;
+    fn apply(&self, x: &[f64]) -> Vec<f64>;
+}
+
+impl LinearOperator for Matrix<f64> {
+    fn dim(&self) -> usize {
+        self.len()
+    }
+
+    fn apply(&self, x: &[f64]) -> Vec<f64> {
+        self.iter().map(|row| dot(row, x)).collect()
+    }
+}
+
+
+/**
+ * A square matrix whose entries can be visited row by row, skipping zeros when it is sparse
+ * This is what the stationary methods need from the matrix of the system
+ */
+pub trait RowMatrix: LinearOperator {
+    fn row_entries(&self, i: usize) -> Box<dyn Iterator<Item = (usize, f64)> + '_>;
+
+    fn diagonal(&self) -> Vec<f64> {
+        (0..self.dim())
+            .map(|i| self.row_entries(i).find(|(j, _)| *j == i).map_or(0.0, |(_, el)| el))
+            .collect()
+    }
+}
+
+impl RowMatrix for Matrix<f64> {
+    fn row_entries(&self, i: usize) -> Box<dyn Iterator<Item = (usize, f64)> + '_> {
+        Box::new(self[i].iter().copied().enumerate())
+    }
+}
+
+
+/**
+ * Preconditioned conjugate gradient method for symmetric positive definite systems
+ * The preconditioner M must also be symmetric positive definite
+ * Stops on ||r[k]|| / ||b|| < tolerance, or on the step size when options.criterion is Step
+ */
+pub fn conjugate_gradient_solver(a: &dyn LinearOperator, b: &[f64], preconditioner: Option<&dyn Preconditioner>, options: &IterativeOptions) -> IterativeReport {
+    let n = a.dim();
+    let b_norm = f64::max(vecnorm(b), f64::MIN_POSITIVE);
+    let precondition = |r: &[f64]| preconditioner.map_or_else(|| r.to_vec(), |m| m.apply(r));
+
+    let mut x = options.initial_guess.clone().unwrap_or_else(|| vec![0.0; n]);
+    let mut r = residual(a, b, &x);
+    let mut z = precondition(&r);
+    let mut p = z.clone();
+    let mut rz = dot(&r, &z);
+    let mut residual_history = vec![vecnorm(&r)];
+    let mut iterations = 0;
+    let mut converged = residual_history[0] / b_norm < options.tolerance;
+
+    while !converged && iterations < options.max_iterations {
+        iterations += 1;
+
+        let ap = a.apply(&p);
+        let alpha = rz / dot(&p, &ap);
+
+        if !alpha.is_finite() {
+            break;
+        }
+
+        axpy(alpha, &p, &mut x);
+        axpy(-alpha, &ap, &mut r);
+
+        let r_norm = vecnorm(&r);
+        residual_history.push(r_norm);
+        converged = match options.criterion {
+            StoppingCriterion::Residual => r_norm / b_norm < options.tolerance,
+            StoppingCriterion::Step => f64::abs(alpha) * vecnorm(&p) / f64::max(vecnorm(&x), f64::MIN_POSITIVE) < options.tolerance,
+        };
+
+        z = precondition(&r);
+        let rz_next = dot(&r, &z);
+        let beta = rz_next / rz;
+        rz = rz_next;
+
+        for (pi, zi) in p.iter_mut().zip(&z) {
+            *pi = zi + beta * *pi;
+        }
+    }
+
+    krylov_report(x, iterations, converged, residual_history)
+}
+
+
+/**
+ * Restarted generalized minimal residual method, GMRES(m), for general nonsingular systems
+ * Builds an orthonormal Krylov basis with the modified Gram-Schmidt process (Arnoldi) and
+ * minimizes the residual with Givens rotations, restarting after m basis vectors
+ * The preconditioner is applied on the right: A M^-1 u = b, x = M^-1 u
+ * Always stops on ||r[k]|| / ||b|| < tolerance, iterations counts the Arnoldi steps
+ */
+pub fn gmres_solver(a: &dyn LinearOperator, b: &[f64], m: usize, preconditioner: Option<&dyn Preconditioner>, options: &IterativeOptions) -> IterativeReport {
+    let n = a.dim();
+    let m = m.clamp(1, n.max(1));
+    let b_norm = f64::max(vecnorm(b), f64::MIN_POSITIVE);
+    let precondition = |r: &[f64]| preconditioner.map_or_else(|| r.to_vec(), |p| p.apply(r));
+
+    let mut x = options.initial_guess.clone().unwrap_or_else(|| vec![0.0; n]);
+    let mut r = residual(a, b, &x);
+    let mut residual_history = vec![vecnorm(&r)];
+    let mut iterations = 0;
+    let mut converged = residual_history[0] / b_norm < options.tolerance;
+
+    while !converged && iterations < options.max_iterations {
+        let beta = vecnorm(&r);
+        let mut v: Matrix<f64> = vec![r.iter().map(|ri| ri / beta).collect()];
+        let mut h = vec![vec![0.0; m]; m + 1];
+        let mut cs = vec![0.0; m];
+        let mut sn = vec![0.0; m];
+        let mut g = vec![0.0; m + 1];
+        g[0] = beta;
+        let mut k = 0;
+
+        while k < m && iterations < options.max_iterations {
+            iterations += 1;
+
+            let mut w = a.apply(&precondition(&v[k]));
+
+            for i in 0..=k {
+                h[i][k] = dot(&w, &v[i]);
+                axpy(-h[i][k], &v[i], &mut w);
+            }
+
+            let w_norm = vecnorm(&w);
+            h[k + 1][k] = w_norm;
+
+            // applies the previous rotations to the new column of the Hessenberg matrix
+            for i in 0..k {
+                let tmp = cs[i] * h[i][k] + sn[i] * h[i + 1][k];
+                h[i + 1][k] = -sn[i] * h[i][k] + cs[i] * h[i + 1][k];
+                h[i][k] = tmp;
+            }
+
+            let rho = f64::hypot(h[k][k], h[k + 1][k]);
+            cs[k] = h[k][k] / rho;
+            sn[k] = h[k + 1][k] / rho;
+            h[k][k] = rho;
+            h[k + 1][k] = 0.0;
+            g[k + 1] = -sn[k] * g[k];
+            g[k] *= cs[k];
+
+            // the Krylov subspace is invariant, so the current iterate is exact
+            let happy_breakdown = w_norm <= f64::EPSILON * rho;
+            v.push(w.iter().map(|wi| wi / w_norm).collect());
+            k += 1;
+
+            let r_norm = f64::abs(g[k]);
+            residual_history.push(r_norm);
+
+            if r_norm / b_norm < options.tolerance || happy_breakdown {
+                converged = r_norm / b_norm < options.tolerance;
+                break;
+            }
+        }
+
+        // solves the k x k upper triangular system H y = g by back substitution
+        let mut y = vec![0.0; k];
+
+        for i in (0..k).rev() {
+            let mut sum = g[i];
+
+            for j in (i + 1)..k {
+                sum -= h[i][j] * y[j];
             }
 
-            r += b_star[i];
-            x1[i] = r;
+            y[i] = sum / h[i][i];
+        }
+
+        let mut update = vec![0.0; n];
+
+        for (yi, vi) in y.iter().zip(&v) {
+            axpy(*yi, vi, &mut update);
+        }
+
+        axpy(1.0, &precondition(&update), &mut x);
+        r = residual(a, b, &x);
+
+        let r_norm = vecnorm(&r);
+        converged = r_norm / b_norm < options.tolerance;
+
+        if let Some(last) = residual_history.last_mut() {
+            *last = r_norm;
         }
 
-        if vecnorm(&subvec(&x1, &x0)) / vecnorm(&x1) < epsilon {
+        if !r_norm.is_finite() || k == 0 {
             break;
         }
+    }
 
-        x0 = x1.clone();
+    krylov_report(x, iterations, converged, residual_history)
+}
+
+
+/**
+ * Biconjugate gradient stabilized method for general nonsingular systems
+ * The preconditioner is applied on the right
+ * Stops on ||r[k]|| / ||b|| < tolerance, or on the step size when options.criterion is Step
+ */
+pub fn bicgstab_solver(a: &dyn LinearOperator, b: &[f64], preconditioner: Option<&dyn Preconditioner>, options: &IterativeOptions) -> IterativeReport {
+    let n = a.dim();
+    let b_norm = f64::max(vecnorm(b), f64::MIN_POSITIVE);
+    let precondition = |r: &[f64]| preconditioner.map_or_else(|| r.to_vec(), |m| m.apply(r));
+
+    let mut x = options.initial_guess.clone().unwrap_or_else(|| vec![0.0; n]);
+    let mut r = residual(a, b, &x);
+    let r_hat = r.clone();
+    let mut p = vec![0.0; n];
+    let mut v = vec![0.0; n];
+    let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+    let mut residual_history = vec![vecnorm(&r)];
+    let mut iterations = 0;
+    let mut converged = residual_history[0] / b_norm < options.tolerance;
+
+    while !converged && iterations < options.max_iterations {
+        iterations += 1;
+
+        let rho_next = dot(&r_hat, &r);
+        let beta = (rho_next / rho) * (alpha / omega);
+        rho = rho_next;
+
+        if !beta.is_finite() {
+            break;
+        }
+
+        for i in 0..n {
+            p[i] = r[i] + beta * (p[i] - omega * v[i]);
+        }
+
+        let p_hat = precondition(&p);
+        v = a.apply(&p_hat);
+        alpha = rho / dot(&r_hat, &v);
+
+        if !alpha.is_finite() {
+            break;
+        }
+
+        let mut s = r.clone();
+        axpy(-alpha, &v, &mut s);
+
+        let s_hat = precondition(&s);
+        let t = a.apply(&s_hat);
+        let t_t = dot(&t, &t);
+        omega = if t_t > 0.0 { dot(&t, &s) / t_t } else { 0.0 };
+
+        let x_norm = vecnorm(&x);
+        axpy(alpha, &p_hat, &mut x);
+        axpy(omega, &s_hat, &mut x);
+
+        r = s;
+        axpy(-omega, &t, &mut r);
+
+        let r_norm = vecnorm(&r);
+        residual_history.push(r_norm);
+        converged = match options.criterion {
+            StoppingCriterion::Residual => r_norm / b_norm < options.tolerance,
+            StoppingCriterion::Step => {
+                let step: Vec<f64> = p_hat.iter().zip(&s_hat).map(|(pi, si)| alpha * pi + omega * si).collect();
+                vecnorm(&step) / f64::max(x_norm, f64::MIN_POSITIVE) < options.tolerance
+            }
+        };
+
+        if omega == 0.0 || !r_norm.is_finite() {
+            break;
+        }
     }
 
-    x1
+    krylov_report(x, iterations, converged, residual_history)
+}
+
+
+/**
+ * y = y + alpha * x
+ */
+fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+    for (yi, xi) in y.iter_mut().zip(x) {
+        *yi += alpha * xi;
+    }
+}
+
+
+/**
+ * r = b - Ax
+ */
+fn residual<A: LinearOperator + ?Sized>(a: &A, b: &[f64], x: &[f64]) -> Vec<f64> {
+    b.iter().zip(a.apply(x)).map(|(bi, axi)| bi - axi).collect()
+}
+
+
+/**
+ * The Krylov solvers do not check any sufficient convergence criterion beforehand
+ */
+fn krylov_report(solution: Vec<f64>, iterations: usize, converged: bool, residual_history: Vec<f64>) -> IterativeReport {
+    IterativeReport {
+        solution,
+        iterations,
+        converged,
+        convergence_guaranteed: false,
+        residual_history,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Tridiagonal matrix tridiag(-1, 2, -1) of the 1-D Poisson problem with n interior points
+     */
+    fn poisson_1d(n: usize) -> Matrix<f64> {
+        (0..n)
+            .map(|i| (0..n).map(|j| if i == j { 2.0 } else if i.abs_diff(j) == 1 { -1.0 } else { 0.0 }).collect())
+            .collect()
+    }
+
+    fn max_residual(a: &Matrix<f64>, b: &[f64], x: &[f64]) -> f64 {
+        residual(a, b, x).iter().fold(0.0, |acc: f64, r| acc.max(f64::abs(*r)))
+    }
+
+    #[test]
+    fn optimal_relaxation_factor_of_the_poisson_matrix() {
+        let n = 20;
+        let h = 1.0 / (n + 1) as f64;
+        let expected = 2.0 / (1.0 + f64::sin(std::f64::consts::PI * h));
+
+        assert!(f64::abs(optimal_relaxation_factor(&poisson_1d(n)) - expected) < 1e-6);
+    }
+
+    #[test]
+    fn sor_at_the_optimal_factor_beats_gauss_seidel() {
+        let n = 20;
+        let a = poisson_1d(n);
+        let b: Vec<f64> = (0..n).map(|i| f64::sin(i as f64)).collect();
+        let options = IterativeOptions { max_iterations: 10_000, ..IterativeOptions::default() };
+
+        let gauss_seidel = sor_solver(&a, &b, Some(1.0), &options);
+        let sor = sor_solver(&a, &b, None, &options);
+        let ssor = ssor_solver(&a, &b, None, &options);
+
+        assert!(gauss_seidel.converged && sor.converged && ssor.converged);
+        assert!(sor.convergence_guaranteed && ssor.convergence_guaranteed);
+        assert!(4 * sor.iterations < gauss_seidel.iterations);
+        assert!(ssor.iterations < gauss_seidel.iterations);
+
+        for report in [gauss_seidel, sor, ssor] {
+            assert!(max_residual(&a, &b, &report.solution) < 1e-9);
+        }
+    }
+
+    /**
+     * tridiag(-1 - convection, 2 + shift, -1 + convection), nonsymmetric when convection != 0
+     */
+    fn convection_diffusion(n: usize, shift: f64, convection: f64) -> Matrix<f64> {
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| match j as i64 - i as i64 {
+                        0 => 2.0 + shift,
+                        -1 => -1.0 - convection,
+                        1 => -1.0 + convection,
+                        _ => 0.0,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /**
+     * Checks the reported convergence against the true residual and the history
+     */
+    fn assert_krylov_report(a: &dyn LinearOperator, b: &[f64], report: &IterativeReport, max_iterations: usize) {
+        let b_norm = vecnorm(b);
+
+        assert!(report.converged);
+        assert!(report.iterations <= max_iterations, "{} iterations", report.iterations);
+        assert!(vecnorm(&residual(a, b, &report.solution)) < 1e-9 * b_norm);
+        assert!(report.residual_history.last().is_some_and(|r| *r < 1e-10 * b_norm));
+    }
+
+    #[test]
+    fn conjugate_gradient_solves_spd_systems_in_at_most_n_steps() {
+        let n = 40;
+        let a = poisson_1d(n);
+        let b: Vec<f64> = (0..n).map(|i| f64::sin(i as f64)).collect();
+
+        let report = conjugate_gradient_solver(&a, &b, None, &IterativeOptions::default());
+
+        assert_krylov_report(&a, &b, &report, n);
+        assert_eq!(report.residual_history.len(), report.iterations + 1);
+    }
+
+    #[test]
+    fn gmres_and_bicgstab_solve_nonsymmetric_systems() {
+        let n = 40;
+        let a = convection_diffusion(n, 0.5, 0.4);
+        let b: Vec<f64> = (0..n).map(|i| f64::cos(i as f64)).collect();
+        let options = IterativeOptions::default();
+
+        let full = gmres_solver(&a, &b, n, None, &options);
+        let restarted = gmres_solver(&a, &b, 5, None, &options);
+        let bicgstab = bicgstab_solver(&a, &b, None, &options);
+
+        assert_krylov_report(&a, &b, &full, n);
+        assert_krylov_report(&a, &b, &restarted, options.max_iterations);
+        assert_krylov_report(&a, &b, &bicgstab, n);
+
+        // restarting discards the Krylov space, it can only need more steps
+        assert!(restarted.iterations >= full.iterations);
+    }
+
+    /**
+     * Matrix-free tridiag(-1, 3, -1), only the product is known
+     */
+    struct MatrixFree {
+        n: usize,
+    }
+
+    impl LinearOperator for MatrixFree {
+        fn dim(&self) -> usize {
+            self.n
+        }
+
+        fn apply(&self, x: &[f64]) -> Vec<f64> {
+            (0..self.n)
+                .map(|i| {
+                    let left = if i > 0 { x[i - 1] } else { 0.0 };
+                    let right = if i + 1 < self.n { x[i + 1] } else { 0.0 };
+                    3.0 * x[i] - left - right
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn krylov_solvers_accept_a_matrix_free_operator() {
+        let operator = MatrixFree { n: 30 };
+        let dense = convection_diffusion(30, 1.0, 0.0);
+        let b: Vec<f64> = (0..30).map(|i| i as f64).collect();
+        let options = IterativeOptions::default();
+
+        let reports = [
+            conjugate_gradient_solver(&operator, &b, None, &options),
+            gmres_solver(&operator, &b, 10, None, &options),
+            bicgstab_solver(&operator, &b, None, &options),
+        ];
+
+        for report in &reports {
+            assert_krylov_report(&operator, &b, report, 30);
+            assert!(vecnorm(&residual(&dense, &b, &report.solution)) < 1e-9 * vecnorm(&b));
+        }
+    }
+
+    #[test]
+    fn max_iterations_stops_a_divergent_iteration() {
+        // the Jacobi iteration matrix has spectral radius sqrt(6)
+        let a = vec![vec![1.0, 2.0], vec![3.0, 1.0]];
+        let b = vec![1.0, 1.0];
+        let options = IterativeOptions { max_iterations: 20, ..IterativeOptions::default() };
+
+        let report = jacobi_richardson_solver(&a, &b, &options);
+
+        assert!(!report.converged && !report.convergence_guaranteed);
+        assert_eq!(report.iterations, 20);
+        assert_eq!(report.residual_history.len(), 21);
+        assert!(report.residual_history[20] > report.residual_history[0]);
+    }
+
+    #[test]
+    fn residual_history_decreases_on_a_diagonally_dominant_system() {
+        // constant diagonal and symmetric: r[k + 1] = ( I - A / 4 ) r[k] with ||I - A / 4||_2 < 1
+        let a = vec![
+            vec![4.0, -1.0, 0.0, -1.0, 0.0],
+            vec![-1.0, 4.0, -1.0, 0.0, -1.0],
+            vec![0.0, -1.0, 4.0, -1.0, 0.0],
+            vec![-1.0, 0.0, -1.0, 4.0, -1.0],
+            vec![0.0, -1.0, 0.0, -1.0, 4.0],
+        ];
+        let b = vec![1.0, -2.0, 3.0, 0.5, 1.0];
+
+        let report = jacobi_richardson_solver(&a, &b, &IterativeOptions::default());
+
+        assert!(report.converged && report.convergence_guaranteed);
+        assert_eq!(report.residual_history.len(), report.iterations + 1);
+        assert!(report.residual_history.windows(2).all(|pair| pair[1] < pair[0]));
+
+        let step = IterativeOptions { criterion: StoppingCriterion::Step, ..IterativeOptions::default() };
+        let report = gauss_seidel_solver(&a, &b, &step);
+        assert!(report.converged && max_residual(&a, &b, &report.solution) < 1e-9);
+    }
+
+    #[test]
+    fn convergence_criteria_on_known_matrices() {
+        let row_dominant = vec![vec![4.0, 1.0, 2.0], vec![1.0, 5.0, 3.0], vec![0.5, 0.5, 2.0]];
+
+        // row 2 is not dominant, the columns and the Sassenfeld factors are (0.5, 0.9375, 0.14375)
+        let column_dominant = vec![vec![4.0, 1.0, 1.0], vec![0.5, 4.0, 3.5], vec![0.5, 0.5, 5.0]];
+
+        let divergent = vec![vec![1.0, 2.0], vec![3.0, 1.0]];
+
+        assert!(row_criterion(&row_dominant));
+        assert!(sassenfeld_criterion(&row_dominant));
+        assert!(!column_criterion(&row_dominant));
+
+        assert!(!row_criterion(&column_dominant));
+        assert!(column_criterion(&column_dominant));
+        assert!(sassenfeld_criterion(&column_dominant));
+
+        assert!(!row_criterion(&divergent));
+        assert!(!column_criterion(&divergent));
+        assert!(!sassenfeld_criterion(&divergent));
+    }
 }
@@ -1,7 +1,8 @@
-use crate::types::Matrix;
+use crate::calculus::differentiation::jacobian;
+use crate::types::{Matrix, MatrixFunction, VectorFunction};
 use super::{
-    exact_methods::lu_solver,
-    helpers::{matmat, matvec, mean, transpose}
+    exact_methods::{cholesky_solver, inverse, lu_solver},
+    helpers::{dot, matmat, matvec, mean, transpose, vecnorm}
 };
 
 /**
@@ -40,3 +41,311 @@ pub fn linear_multiple_regression(x: &Matrix<f64>, y: &Vec<f64>, n: u8) -> Vec<f
 
     lu_solver(&x_t_x, &x_t_y)
 }
+
+
+/**
+ * Options shared by the nonlinear least squares solvers
+ * The bounds, when given, must have the same length as the parameters vector
+ */
+pub struct NonlinearLeastSquaresOptions {
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    pub lower_bounds: Option<Vec<f64>>,
+    pub upper_bounds: Option<Vec<f64>>,
+}
+
+impl Default for NonlinearLeastSquaresOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-10,
+            max_iterations: 200,
+            lower_bounds: None,
+            upper_bounds: None,
+        }
+    }
+}
+
+
+/**
+ * Result of a nonlinear least squares fit
+ * residual_norm is ||r(p)|| at the returned parameters
+ * covariance is s² (J_F^T J_F)^-1 over the free parameters F, with s² = ||r||² / (m - n_F),
+ * the rows and columns of the parameters held at an active bound are zero
+ */
+pub struct NonlinearLeastSquaresReport {
+    pub parameters: Vec<f64>,
+    pub residual_norm: f64,
+    pub iterations: usize,
+    pub converged: bool,
+    pub covariance: Matrix<f64>,
+}
+
+
+/**
+ * Fits the parameters p that minimize ||r(p)||² using the Gauss-Newton method
+ * (J^T J) delta = -J^T r, halving the step until the residual decreases
+ * When jac is None the Jacobian of the residuals is approximated by finite differences
+ */
+pub fn gauss_newton(
+    residual: &VectorFunction,
+    jac: Option<&MatrixFunction>,
+    p0: &[f64],
+    options: &NonlinearLeastSquaresOptions,
+) -> NonlinearLeastSquaresReport {
+    nonlinear_least_squares(residual, jac, p0, options, false)
+}
+
+
+/**
+ * Fits the parameters p that minimize ||r(p)||² using the Levenberg-Marquardt method
+ * (J^T J + lambda * diag(J^T J)) delta = -J^T r
+ * lambda is decreased when a step reduces the residual and increased otherwise
+ * When jac is None the Jacobian of the residuals is approximated by finite differences
+ */
+pub fn levenberg_marquardt(
+    residual: &VectorFunction,
+    jac: Option<&MatrixFunction>,
+    p0: &[f64],
+    options: &NonlinearLeastSquaresOptions,
+) -> NonlinearLeastSquaresReport {
+    nonlinear_least_squares(residual, jac, p0, options, true)
+}
+
+
+/**
+ * Active set iteration shared by both solvers
+ * A parameter is active when it sits on a bound and the gradient g = J^T r pushes it outwards
+ * (p_i = lower_i and g_i > 0, or p_i = upper_i and g_i < 0). Active parameters stay fixed and
+ * the normal equations are solved over the free ones only, the candidate is projected onto the bounds
+ * Converged when the projected gradient g_F vanishes: max |g_F| < tolerance, or the full Gauss-Newton
+ * step over the free parameters, (J_F^T J_F)^-1 g_F, is below tolerance * ||p_F||. The projected step
+ * that is actually taken is never tested, it shrinks to zero against a bound away from the optimum
+ */
+fn nonlinear_least_squares(
+    residual: &VectorFunction,
+    jac: Option<&MatrixFunction>,
+    p0: &[f64],
+    options: &NonlinearLeastSquaresOptions,
+    damped: bool,
+) -> NonlinearLeastSquaresReport {
+    let eval_jacobian = |p: &[f64]| match jac {
+        Some(jac) => jac(p),
+        None => jacobian(residual, p),
+    };
+
+    let mut p = project_onto_bounds(p0, options);
+    let mut r = residual(&p);
+    let mut cost = dot(&r, &r);
+    let mut lambda = 1e-3;
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < options.max_iterations {
+        iterations += 1;
+
+        let j = eval_jacobian(&p);
+        let j_t = transpose(&j);
+        let j_t_j = matmat(&j_t, &j);
+        let gradient = matvec(&j_t, &r);
+        let free = free_parameters(&p, &gradient, options);
+
+        if free.iter().all(|i| f64::abs(gradient[*i]) < options.tolerance) {
+            converged = true;
+            break;
+        }
+
+        let reduced_j_t_j = submatrix(&j_t_j, &free);
+        let minus_gradient: Vec<f64> = free.iter().map(|i| -gradient[*i]).collect();
+        let gauss_newton_step = cholesky_solver(&reduced_j_t_j, &minus_gradient);
+        let p_free: Vec<f64> = free.iter().map(|i| p[*i]).collect();
+
+        if vecnorm(&gauss_newton_step) < options.tolerance * (vecnorm(&p_free) + options.tolerance) {
+            converged = true;
+            break;
+        }
+
+        let mut alpha = 1.;
+
+        let accepted = loop {
+            let delta = if damped {
+                let mut system = reduced_j_t_j.clone();
+
+                for (i, row) in system.iter_mut().enumerate() {
+                    row[i] += lambda * f64::max(reduced_j_t_j[i][i], f64::EPSILON);
+                }
+
+                cholesky_solver(&system, &minus_gradient)
+            } else {
+                gauss_newton_step.iter().map(|d| alpha * d).collect()
+            };
+
+            let mut candidate = p.clone();
+
+            for (i, d) in free.iter().zip(&delta) {
+                candidate[*i] += d;
+            }
+
+            let candidate = project_onto_bounds(&candidate, options);
+            let r_candidate = residual(&candidate);
+            let cost_candidate = dot(&r_candidate, &r_candidate);
+
+            if cost_candidate.is_finite() && cost_candidate <= cost {
+                break Some((candidate, r_candidate, cost_candidate));
+            }
+
+            if damped && lambda < 1e16 {
+                lambda *= 10.;
+            } else if !damped && alpha > 1e-10 {
+                alpha /= 2.;
+            } else {
+                break None;
+            }
+        };
+
+        let Some((candidate, r_candidate, cost_candidate)) = accepted else {
+            break; // no descent step is left, converged stays false
+        };
+
+        p = candidate;
+        r = r_candidate;
+        cost = cost_candidate;
+        lambda = f64::max(lambda / 10., 1e-12);
+    }
+
+    let j = eval_jacobian(&p);
+    let j_t = transpose(&j);
+    let gradient = matvec(&j_t, &r);
+    let free = free_parameters(&p, &gradient, options);
+    report(p, r, iterations, converged, &matmat(&j_t, &j), &free)
+}
+
+
+fn report(parameters: Vec<f64>, r: Vec<f64>, iterations: usize, converged: bool, j_t_j: &Matrix<f64>, free: &[usize]) -> NonlinearLeastSquaresReport {
+    let m = r.len();
+    let n = parameters.len();
+    let residual_norm = vecnorm(&r);
+    let s2 = if m > free.len() { residual_norm * residual_norm / (m - free.len()) as f64 } else { f64::NAN };
+    let reduced_inverse = inverse(&submatrix(j_t_j, free));
+    let mut covariance = vec![vec![0.; n]; n];
+
+    for (a, i) in free.iter().enumerate() {
+        for (b, k) in free.iter().enumerate() {
+            covariance[*i][*k] = reduced_inverse[a][b] * s2;
+        }
+    }
+
+    NonlinearLeastSquaresReport { parameters, residual_norm, iterations, converged, covariance }
+}
+
+
+/**
+ * Indices of the parameters that are not held by an active bound
+ */
+fn free_parameters(p: &[f64], gradient: &[f64], options: &NonlinearLeastSquaresOptions) -> Vec<usize> {
+    (0..p.len())
+        .filter(|i| {
+            let at_lower = options.lower_bounds.as_ref().is_some_and(|l| p[*i] <= l[*i]);
+            let at_upper = options.upper_bounds.as_ref().is_some_and(|u| p[*i] >= u[*i]);
+            !(at_lower && gradient[*i] > 0. || at_upper && gradient[*i] < 0.)
+        })
+        .collect()
+}
+
+
+fn submatrix(a: &Matrix<f64>, indices: &[usize]) -> Matrix<f64> {
+    indices.iter().map(|i| indices.iter().map(|k| a[*i][*k]).collect()).collect()
+}
+
+
+fn project_onto_bounds(p: &[f64], options: &NonlinearLeastSquaresOptions) -> Vec<f64> {
+    p.iter()
+        .enumerate()
+        .map(|(i, pi)| {
+            let lower = options.lower_bounds.as_ref().map_or(f64::NEG_INFINITY, |l| l[i]);
+            let upper = options.upper_bounds.as_ref().map_or(f64::INFINITY, |u| u[i]);
+            pi.clamp(lower, upper)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: [f64; 7] = [0., 0.5, 1., 1.5, 2., 2.5, 3.];
+
+    fn exponential_residual(y: &[f64]) -> impl Fn(&[f64]) -> Vec<f64> + '_ {
+        move |p: &[f64]| X.iter().zip(y).map(|(x, yi)| p[0] * f64::exp(p[1] * x) - yi).collect()
+    }
+
+    fn exponential_jacobian(p: &[f64]) -> Matrix<f64> {
+        X.iter().map(|x| vec![f64::exp(p[1] * x), p[0] * x * f64::exp(p[1] * x)]).collect()
+    }
+
+    fn noisy_data() -> Vec<f64> {
+        let noise = [0.05, -0.08, 0.03, 0.1, -0.06, 0.02, -0.04];
+        X.iter().zip(noise).map(|(x, e)| 2. * f64::exp(0.8 * x) + e).collect()
+    }
+
+    #[test]
+    fn fits_exact_data_without_bounds() {
+        let y: Vec<f64> = X.iter().map(|x| 2. * f64::exp(0.8 * x)).collect();
+        let residual = exponential_residual(&y);
+        let options = NonlinearLeastSquaresOptions::default();
+
+        for report in [
+            gauss_newton(&residual, None, &[1., 0.], &options),
+            levenberg_marquardt(&residual, Some(&exponential_jacobian), &[1., 0.], &options),
+        ] {
+            assert!(report.converged);
+            assert!(f64::abs(report.parameters[0] - 2.) < 1e-6);
+            assert!(f64::abs(report.parameters[1] - 0.8) < 1e-6);
+            assert!(report.residual_norm < 1e-6);
+        }
+    }
+
+    #[test]
+    fn inactive_bounds_do_not_change_the_optimum() {
+        let y = noisy_data();
+        let residual = exponential_residual(&y);
+        let free = levenberg_marquardt(&residual, Some(&exponential_jacobian), &[1., 0.], &NonlinearLeastSquaresOptions::default());
+
+        let options = NonlinearLeastSquaresOptions { lower_bounds: Some(vec![0., 0.]), upper_bounds: Some(vec![10., 2.]), ..Default::default() };
+        let bounded = gauss_newton(&residual, Some(&exponential_jacobian), &[1., 0.], &options);
+
+        assert!(free.converged && bounded.converged);
+        assert!(f64::abs(free.parameters[0] - bounded.parameters[0]) < 1e-8);
+        assert!(f64::abs(free.parameters[1] - bounded.parameters[1]) < 1e-8);
+        assert!(free.covariance[0][0] > 0. && free.covariance[1][1] > 0.);
+    }
+
+    #[test]
+    fn reaches_the_constrained_optimum_on_an_active_bound() {
+        let y = noisy_data();
+        let residual = exponential_residual(&y);
+
+        // with b = 0.5 fixed the model is linear in a
+        let b = 0.5;
+        let a = X.iter().zip(&y).map(|(x, yi)| yi * f64::exp(b * x)).sum::<f64>() / X.iter().map(|x| f64::exp(2. * b * x)).sum::<f64>();
+        let optimal_cost = dot(&residual(&[a, b]), &residual(&[a, b]));
+
+        let options = NonlinearLeastSquaresOptions { upper_bounds: Some(vec![f64::INFINITY, b]), ..Default::default() };
+
+        for report in [
+            gauss_newton(&residual, None, &[1., 0.], &options),
+            levenberg_marquardt(&residual, None, &[1., 0.], &options),
+            levenberg_marquardt(&residual, Some(&exponential_jacobian), &[5., 0.5], &options),
+        ] {
+            assert!(report.converged);
+            assert_eq!(report.parameters[1], b);
+            assert!(f64::abs(report.parameters[0] - a) < 1e-6);
+            assert!(f64::abs(report.residual_norm * report.residual_norm - optimal_cost) < 1e-8);
+
+            // b is held by the bound, it has no variance
+            assert!(report.covariance[0][0] > 0.);
+            assert_eq!(report.covariance[1][1], 0.);
+            assert_eq!(report.covariance[0][1], 0.);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use crate::types::Matrix;
+use super::exact_methods::LuFactorization;
+use super::helpers::{get_identity_matrix, infinity_norm, matmat, transpose};
+
+
+const MAX_SQRT_ITERATIONS: usize = 100;
+const MAX_LOG_SQUARE_ROOTS: usize = 64;
+// ||E||_inf <= 1 / 4 makes the terms of log(I + E) shrink by 4 at least, 40 terms reach 1e-24
+const MAX_LOG_SERIES_TERMS: usize = 40;
+
+
+/**
+ * Matrix exponential e^A with scaling and squaring and the diagonal (6, 6) Padé approximant
+ * A is scaled by 2^-j so that ||A / 2^j||_inf <= 1 / 2, then
+ * e^A = ( D^-1 N )^(2^j), N = sum c_k X^k, D = sum (-1)^k c_k X^k, X = A / 2^j
+ * The relative error of the approximant is below 3.4e-16 (Golub, Van Loan)
+ * For the solution of x' = A x, x(t) = expm(A t) x(0)
+ * Returns a matrix of NaN when A has a non-finite entry or its norm overflows
+ */
+pub fn expm(a: &Matrix<f64>) -> Matrix<f64> {
+    let n = a.len();
+    let q = 6;
+    let norm = infinity_norm(a);
+
+    if !is_finite(a) || !norm.is_finite() {
+        return vec![vec![f64::NAN; n]; n];
+    }
+
+    // norm < 2^(floor(log2 norm) + 1), so 2^-j norm < 1 / 2
+    let j = if norm > 0. { i32::max(0, 2 + f64::log2(norm).floor() as i32) } else { 0 };
+    let x = scale(a, 0.5f64.powi(j));
+
+    let mut numerator = get_identity_matrix(n);
+    let mut denominator = get_identity_matrix(n);
+    let mut power = get_identity_matrix(n);
+    let mut c = 1.;
+
+    for k in 1..=q {
+        c *= (q - k + 1) as f64 / ((2 * q - k + 1) * k) as f64;
+        power = matmat(&x, &power);
+        let sign = if k % 2 == 0 { 1. } else { -1. };
+
+        for ((num_row, den_row), power_row) in numerator.iter_mut().zip(denominator.iter_mut()).zip(&power) {
+            for ((num, den), p) in num_row.iter_mut().zip(den_row.iter_mut()).zip(power_row) {
+                *num += c * p;
+                *den += sign * c * p;
+            }
+        }
+    }
+
+    // D is nonsingular for ||X||_inf <= 1 / 2
+    let lu = LuFactorization::new(&denominator).expect("the Padé denominator is singular");
+    let mut f = solve_matrix(&lu, &numerator);
+
+    for _ in 0..j {
+        f = matmat(&f, &f);
+    }
+
+    f
+}
+
+
+/**
+ * Principal square root X of A, X² = A, with the Denman-Beavers iteration
+ * Y_0 = A, Z_0 = I
+ * Y_k+1 = ( mu Y_k + (mu Z_k)^-1 ) / 2, Z_k+1 = ( mu Z_k + (mu Y_k)^-1 ) / 2
+ * Y_k -> A^1/2 and Z_k -> A^-1/2, mu = |det(Y_k) det(Z_k)|^(-1 / 2n) is the determinant scaling,
+ * used until the steps become small
+ * A must not have eigenvalues on the closed negative real axis
+ * Returns None when A has a non-finite entry, an iterate is singular or the iteration does not converge
+ */
+pub fn sqrtm(a: &Matrix<f64>) -> Option<Matrix<f64>> {
+    let n = a.len();
+
+    if !is_finite(a) {
+        return None;
+    }
+
+    let mut y = a.clone();
+    let mut z = get_identity_matrix(n);
+    let mut previous_step = f64::INFINITY;
+
+    for _ in 0..MAX_SQRT_ITERATIONS {
+        let y_lu = LuFactorization::new(&y)?;
+        let z_lu = LuFactorization::new(&z)?;
+
+        // the scaling only speeds up the first iterations, near the root it adds rounding noise
+        let det_product = f64::abs(y_lu.det() * z_lu.det());
+        let scaled = previous_step > 1e-2 * infinity_norm(&y) && det_product > 0. && det_product.is_finite();
+        let mu = if scaled { det_product.powf(-0.5 / n as f64) } else { 1. };
+
+        let y_inverse = solve_matrix(&y_lu, &get_identity_matrix(n));
+        let z_inverse = solve_matrix(&z_lu, &get_identity_matrix(n));
+
+        let next_y = combine(&y, 0.5 * mu, &z_inverse, 0.5 / mu);
+        let next_z = combine(&z, 0.5 * mu, &y_inverse, 0.5 / mu);
+
+        let step = infinity_norm(&combine(&next_y, 1., &y, -1.));
+        y = next_y;
+        z = next_z;
+
+        // the convergence is quadratic, a step that stops shrinking is rounding noise
+        let y_norm = infinity_norm(&y);
+        let stagnated = step <= f64::sqrt(f64::EPSILON) * y_norm && step >= 0.5 * previous_step;
+
+        if step <= n as f64 * f64::EPSILON * y_norm || stagnated {
+            return Some(y);
+        }
+
+        previous_step = step;
+    }
+
+    None
+}
+
+
+/**
+ * Principal logarithm of A with inverse scaling and squaring
+ * A^(1/2^k) is computed with sqrtm until ||A^(1/2^k) - I||_inf <= 1 / 4, then
+ * log(A) = 2^k log(I + E), log(I + E) = E - E²/2 + E³/3 - ...
+ * A must not have eigenvalues on the closed negative real axis
+ * Returns None when A has a non-finite entry or a square root fails
+ */
+pub fn logm(a: &Matrix<f64>) -> Option<Matrix<f64>> {
+    let n = a.len();
+
+    // infinity_norm skips the rows whose sum is NaN, the loops below would never meet their conditions
+    if !is_finite(a) {
+        return None;
+    }
+
+    let identity = get_identity_matrix(n);
+    let mut root = a.clone();
+    let mut k = 0;
+
+    while infinity_norm(&combine(&root, 1., &identity, -1.)) > 0.25 {
+        if k == MAX_LOG_SQUARE_ROOTS {
+            return None;
+        }
+
+        root = sqrtm(&root)?;
+        k += 1;
+    }
+
+    let e = combine(&root, 1., &identity, -1.);
+    let mut log = e.clone();
+    let mut power = e.clone();
+
+    for j in 2..=MAX_LOG_SERIES_TERMS {
+        power = matmat(&power, &e);
+        let coefficient = if j % 2 == 0 { -1. / j as f64 } else { 1. / j as f64 };
+        log = combine(&log, 1., &power, coefficient);
+
+        if f64::abs(coefficient) * infinity_norm(&power) <= f64::EPSILON * infinity_norm(&log) {
+            break;
+        }
+    }
+
+    Some(scale(&log, 2f64.powi(k as i32)))
+}
+
+
+/**
+ * A^k for an integer k, with binary powering (O(log |k|) products)
+ * A^0 = I, negative powers invert A first
+ * Returns None when k < 0 and A is singular
+ */
+pub fn matrix_power(a: &Matrix<f64>, k: i64) -> Option<Matrix<f64>> {
+    let n = a.len();
+
+    let mut base = if k < 0 {
+        let lu = LuFactorization::new(a)?;
+        solve_matrix(&lu, &get_identity_matrix(n))
+    } else {
+        a.clone()
+    };
+
+    let mut exponent = k.unsigned_abs();
+    let mut result = get_identity_matrix(n);
+
+    while exponent > 0 {
+        if exponent % 2 == 1 {
+            result = matmat(&result, &base);
+        }
+
+        exponent /= 2;
+
+        if exponent > 0 {
+            base = matmat(&base, &base);
+        }
+    }
+
+    Some(result)
+}
+
+
+/**
+ * A^p for a real p, A^p = e^(p log(A))
+ * Integer powers are computed exactly with matrix_power
+ * A must not have eigenvalues on the closed negative real axis
+ */
+pub fn fractional_matrix_power(a: &Matrix<f64>, p: f64) -> Option<Matrix<f64>> {
+    if p.fract() == 0. && f64::abs(p) <= i64::MAX as f64 {
+        return matrix_power(a, p as i64);
+    }
+
+    if p == 0.5 {
+        return sqrtm(a);
+    }
+
+    let log = logm(a)?;
+    Some(expm(&scale(&log, p)))
+}
+
+
+/**
+ * Solves A X = B column by column with a LU factorization of A
+ */
+fn solve_matrix(lu: &LuFactorization, b: &Matrix<f64>) -> Matrix<f64> {
+    let columns: Matrix<f64> = transpose(b).iter().map(|col| lu.solve(col)).collect();
+    transpose(&columns)
+}
+
+
+/**
+ * Returns alpha A + beta B
+ */
+fn combine(a: &Matrix<f64>, alpha: f64, b: &Matrix<f64>, beta: f64) -> Matrix<f64> {
+    a.iter()
+        .zip(b)
+        .map(|(row_a, row_b)| row_a.iter().zip(row_b).map(|(x, y)| alpha * x + beta * y).collect())
+        .collect()
+}
+
+
+fn scale(a: &Matrix<f64>, alpha: f64) -> Matrix<f64> {
+    a.iter().map(|row| row.iter().map(|x| alpha * x).collect()).collect()
+}
+
+
+fn is_finite(a: &Matrix<f64>) -> bool {
+    a.iter().flatten().all(|x| x.is_finite())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_abs_difference(a: &Matrix<f64>, b: &Matrix<f64>) -> f64 {
+        infinity_norm(&combine(a, 1., b, -1.))
+    }
+
+    #[test]
+    fn expm_of_the_rotation_generator_is_a_rotation() {
+        for t in [0.1, 1., 2.5, 10.] {
+            let generator = vec![vec![0., -t], vec![t, 0.]];
+            let rotation = vec![vec![f64::cos(t), -f64::sin(t)], vec![f64::sin(t), f64::cos(t)]];
+
+            assert!(max_abs_difference(&expm(&generator), &rotation) < 1e-13 * ( 1. + t ), "t = {t}");
+        }
+    }
+
+    #[test]
+    fn expm_of_a_diagonal_and_a_nilpotent_matrix() {
+        let diagonal = vec![vec![1., 0., 0.], vec![0., -2., 0.], vec![0., 0., 0.5]];
+        let expected = vec![vec![f64::exp(1.), 0., 0.], vec![0., f64::exp(-2.), 0.], vec![0., 0., f64::exp(0.5)]];
+        assert!(max_abs_difference(&expm(&diagonal), &expected) < 1e-14 * f64::exp(1.));
+
+        // e^N = I + N + N² / 2 for N³ = 0
+        let nilpotent = vec![vec![0., 1., 2.], vec![0., 0., 3.], vec![0., 0., 0.]];
+        let expected = vec![vec![1., 1., 3.5], vec![0., 1., 3.], vec![0., 0., 1.]];
+        assert!(max_abs_difference(&expm(&nilpotent), &expected) < 1e-14);
+    }
+
+    #[test]
+    fn logm_inverts_expm() {
+        let a = vec![vec![0.5, 1., 0.], vec![-0.3, 0.2, 0.4], vec![0.1, 0., -0.6]];
+        let log = logm(&expm(&a)).expect("e^A has no eigenvalues on the negative real axis");
+
+        assert!(max_abs_difference(&log, &a) < 1e-12);
+    }
+
+    #[test]
+    fn sqrtm_and_fractional_powers_are_consistent() {
+        let a = vec![vec![4., 1., 0.], vec![1., 3., 1.], vec![0., 1., 2.]];
+        let root = sqrtm(&a).expect("A is symmetric positive definite");
+        assert!(max_abs_difference(&matmat(&root, &root), &a) < 1e-12);
+
+        let third = fractional_matrix_power(&a, 1. / 3.).expect("A is symmetric positive definite");
+        let cube = matrix_power(&third, 3).unwrap();
+        assert!(max_abs_difference(&cube, &a) < 1e-11);
+
+        let inverse = matrix_power(&a, -1).unwrap();
+        assert!(max_abs_difference(&matmat(&inverse, &a), &get_identity_matrix(3)) < 1e-14);
+    }
+
+    #[test]
+    fn expm_of_a_non_finite_matrix_is_nan() {
+        let matrices = [
+            vec![vec![f64::INFINITY, 0.], vec![0., 1.]],
+            vec![vec![1., 0.], vec![f64::NAN, 1.]],
+            vec![vec![f64::MAX, f64::MAX], vec![0., 1.]],
+        ];
+
+        for a in matrices {
+            assert!(expm(&a).iter().flatten().all(|x| x.is_nan()));
+        }
+    }
+
+    #[test]
+    fn logm_and_sqrtm_reject_non_finite_matrices() {
+        // the NaN is in the second row, which infinity_norm does not see
+        let nan = vec![vec![2., 0.], vec![0., f64::NAN]];
+        let infinite = vec![vec![f64::INFINITY, 0.], vec![0., 2.]];
+
+        for a in [nan, infinite] {
+            assert!(logm(&a).is_none());
+            assert!(sqrtm(&a).is_none());
+            assert!(fractional_matrix_power(&a, 0.3).is_none());
+        }
+    }
+}
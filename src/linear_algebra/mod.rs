@@ -2,4 +2,8 @@ pub mod eigen;
 pub mod iterative_methods;
 pub mod helpers;
 pub mod exact_methods;
-pub mod least_squares;
\ No newline at end of file
+pub mod least_squares;
+pub mod matrix_functions;
+pub mod preconditioners;
+pub mod sparse;
+pub mod svd;
@@ -0,0 +1,488 @@
+use crate::types::Matrix;
+use super::sparse::CsrMatrix;
+
+
+/**
+ * A preconditioner M approximates the matrix A of a linear system
+ * and apply returns z = M^-1 r cheaply
+ */
+pub trait Preconditioner {
+    fn apply(&self, r: &[f64]) -> Vec<f64>;
+}
+
+
+/**
+ * Jacobi (diagonal) preconditioner
+ * M = D
+ */
+pub struct JacobiPreconditioner {
+    inverse_diagonal: Vec<f64>,
+}
+
+impl JacobiPreconditioner {
+    pub fn new(a: &Matrix<f64>) -> Self {
+        let inverse_diagonal = a.iter().enumerate().map(|(i, row)| 1.0 / row[i]).collect();
+        Self { inverse_diagonal }
+    }
+
+    /**
+     * Only the diagonal of A is read, a missing diagonal entry gives an infinite inverse
+     */
+    pub fn from_csr(a: &CsrMatrix) -> Self {
+        let inverse_diagonal = (0..a.rows()).map(|i| 1.0 / a.get(i, i)).collect();
+        Self { inverse_diagonal }
+    }
+}
+
+impl Preconditioner for JacobiPreconditioner {
+    fn apply(&self, r: &[f64]) -> Vec<f64> {
+        r.iter().zip(&self.inverse_diagonal).map(|(ri, di)| ri * di).collect()
+    }
+}
+
+
+/**
+ * Symmetric successive over-relaxation preconditioner
+ * A = L + D + U
+ * M = ( D + omega * L ) D^-1 ( D + omega * U ) / ( omega * (2 - omega) )
+ * 0 < omega < 2, omega = 1 gives the symmetric Gauss-Seidel preconditioner
+ * A is kept in CSR, the two triangular solves read its lower and upper parts in place
+ */
+pub struct SsorPreconditioner {
+    a: CsrMatrix,
+    diagonal: Vec<f64>,
+    omega: f64,
+}
+
+impl SsorPreconditioner {
+    pub fn new(a: &Matrix<f64>, omega: f64) -> Self {
+        Self::from_csr(&CsrMatrix::from_dense(a), omega)
+    }
+
+    pub fn from_csr(a: &CsrMatrix, omega: f64) -> Self {
+        let diagonal = (0..a.rows()).map(|i| a.get(i, i)).collect();
+
+        Self { a: a.clone(), diagonal, omega }
+    }
+}
+
+impl Preconditioner for SsorPreconditioner {
+    /**
+     * ( D + omega * L ) y = r, then ( D + omega * U ) z = D y, scaled by omega * (2 - omega)
+     */
+    fn apply(&self, r: &[f64]) -> Vec<f64> {
+        let n = r.len();
+        let mut z = r.to_vec();
+
+        for i in 0..n {
+            let (cols, values) = self.a.row(i);
+            let split = cols.partition_point(|j| *j < i);
+            let s: f64 = cols[..split].iter().zip(&values[..split]).map(|(j, aij)| aij * z[*j]).sum();
+            z[i] = (z[i] - self.omega * s) / self.diagonal[i];
+        }
+
+        for (zi, di) in z.iter_mut().zip(&self.diagonal) {
+            *zi *= di;
+        }
+
+        for i in (0..n).rev() {
+            let (cols, values) = self.a.row(i);
+            let split = cols.partition_point(|j| *j <= i);
+            let s: f64 = cols[split..].iter().zip(&values[split..]).map(|(j, aij)| aij * z[*j]).sum();
+            z[i] = (z[i] - self.omega * s) / self.diagonal[i];
+        }
+
+        let scale = self.omega * (2.0 - self.omega);
+
+        z.into_iter().map(|zi| zi * scale).collect()
+    }
+}
+
+
+/**
+ * Incomplete Cholesky factorization with zero fill-in, IC(0)
+ * M = G G^T, where G is lower triangular and g[i][j] = 0 wherever a[i][j] = 0
+ * G is stored in CSR with the diagonal last in every row, the factorization and the
+ * triangular solves only visit the sparsity pattern of A
+ * Returns None when a pivot is not positive, which may happen even for
+ * symmetric positive definite matrices that are not M-matrices
+ */
+pub struct IncompleteCholeskyPreconditioner {
+    g: CsrMatrix,
+}
+
+impl IncompleteCholeskyPreconditioner {
+    pub fn new(a: &Matrix<f64>) -> Option<Self> {
+        Self::from_csr(&CsrMatrix::from_dense(a))
+    }
+
+    /**
+     * Row by row, only the lower triangle of A is read
+     * g[i][j] = ( a[i][j] - sum( g[i][k] g[j][k], k < j ) ) / g[j][j], for the stored j < i
+     * g[i][i] = sqrt( a[i][i] - sum( g[i][k]², k < i ) )
+     * The sums run over the common pattern of the rows i and j of G
+     */
+    pub fn from_csr(a: &CsrMatrix) -> Option<Self> {
+        let n = a.rows();
+        let mut row_ptr = vec![0; n + 1];
+        let mut col_indices: Vec<usize> = Vec::new();
+        let mut values: Vec<f64> = Vec::new();
+
+        for i in 0..n {
+            let start = row_ptr[i];
+            let mut diagonal = 0.0;
+
+            let (cols, a_values) = a.row(i);
+
+            for (j, a_ij) in cols.iter().zip(a_values) {
+                if *j > i {
+                    break;
+                }
+
+                if *j == i {
+                    diagonal = *a_ij;
+                    break;
+                }
+
+                // the row j of G without its diagonal, which is last
+                let row_j = row_ptr[*j]..row_ptr[*j + 1] - 1;
+                let s = sparse_dot(&col_indices[start..], &values[start..], &col_indices[row_j.clone()], &values[row_j]);
+
+                col_indices.push(*j);
+                values.push((a_ij - s) / values[row_ptr[*j + 1] - 1]);
+            }
+
+            let d = diagonal - values[start..].iter().map(|gik| gik * gik).sum::<f64>();
+
+            if d <= 0.0 || !d.is_finite() {
+                return None;
+            }
+
+            col_indices.push(i);
+            values.push(f64::sqrt(d));
+            row_ptr[i + 1] = values.len();
+        }
+
+        Some(Self { g: CsrMatrix::from_parts(n, n, row_ptr, col_indices, values) })
+    }
+}
+
+impl Preconditioner for IncompleteCholeskyPreconditioner {
+    /**
+     * Solves G y = r by rows, then G^T z = y by the columns of G^T, which are the rows of G
+     */
+    fn apply(&self, r: &[f64]) -> Vec<f64> {
+        let n = r.len();
+        let mut z = r.to_vec();
+
+        for i in 0..n {
+            let (cols, values) = self.g.row(i);
+            let (diagonal, off_diagonal) = values.split_last().expect("G has a diagonal in every row");
+            let s: f64 = cols.iter().zip(off_diagonal).map(|(j, gij)| gij * z[*j]).sum();
+            z[i] = (z[i] - s) / diagonal;
+        }
+
+        for i in (0..n).rev() {
+            let (cols, values) = self.g.row(i);
+            let (diagonal, off_diagonal) = values.split_last().expect("G has a diagonal in every row");
+            z[i] /= diagonal;
+
+            for (j, gij) in cols.iter().zip(off_diagonal) {
+                z[*j] -= gij * z[i];
+            }
+        }
+
+        z
+    }
+}
+
+
+/**
+ * Incomplete LU factorization with zero fill-in, ILU(0)
+ * M = LU, where L is unit lower triangular, U is upper triangular
+ * and both keep the sparsity pattern of A
+ * L (without its unit diagonal) and U share one CSR matrix with the pattern of A,
+ * the factorization and the triangular solves only visit that pattern
+ * Returns None on a zero pivot, including a diagonal entry missing from the pattern
+ */
+pub struct IncompleteLuPreconditioner {
+    lu: CsrMatrix,
+}
+
+impl IncompleteLuPreconditioner {
+    pub fn new(a: &Matrix<f64>) -> Option<Self> {
+        Self::from_csr(&CsrMatrix::from_dense(a))
+    }
+
+    /**
+     * IKJ variant: for every row i and every stored k < i in increasing order
+     * l[i][k] = a[i][k] / u[k][k], then a[i][j] -= l[i][k] u[k][j] for the stored j > k
+     * The stored positions of the current row are found in O(1) through a column map
+     */
+    pub fn from_csr(a: &CsrMatrix) -> Option<Self> {
+        let n = a.rows();
+        let mut row_ptr = vec![0; n + 1];
+        let mut col_indices: Vec<usize> = Vec::with_capacity(a.nnz());
+        let mut values: Vec<f64> = Vec::with_capacity(a.nnz());
+
+        for i in 0..n {
+            let (cols, row_values) = a.row(i);
+            col_indices.extend_from_slice(cols);
+            values.extend_from_slice(row_values);
+            row_ptr[i + 1] = values.len();
+        }
+
+        let mut diagonal_positions = vec![0; n];
+        let mut position: Vec<Option<usize>> = vec![None; n];
+
+        for i in 0..n {
+            let row = row_ptr[i]..row_ptr[i + 1];
+
+            for p in row.clone() {
+                position[col_indices[p]] = Some(p);
+            }
+
+            for p in row.clone() {
+                let k = col_indices[p];
+
+                if k >= i {
+                    break;
+                }
+
+                let pivot = values[diagonal_positions[k]];
+                values[p] /= pivot;
+                let l_ik = values[p];
+
+                for q in (diagonal_positions[k] + 1)..row_ptr[k + 1] {
+                    if let Some(target) = position[col_indices[q]] {
+                        values[target] -= l_ik * values[q];
+                    }
+                }
+            }
+
+            match position[i] {
+                Some(p) if values[p] != 0.0 => diagonal_positions[i] = p,
+                _ => return None,
+            }
+
+            for p in row {
+                position[col_indices[p]] = None;
+            }
+        }
+
+        Some(Self { lu: CsrMatrix::from_parts(n, n, row_ptr, col_indices, values) })
+    }
+}
+
+impl Preconditioner for IncompleteLuPreconditioner {
+    fn apply(&self, r: &[f64]) -> Vec<f64> {
+        let n = r.len();
+        let mut z = r.to_vec();
+
+        // L y = r, unit diagonal
+        for i in 0..n {
+            let (cols, values) = self.lu.row(i);
+            let split = cols.partition_point(|j| *j < i);
+            let s: f64 = cols[..split].iter().zip(&values[..split]).map(|(j, lij)| lij * z[*j]).sum();
+            z[i] -= s;
+        }
+
+        // U z = y, u[i][i] is the first entry of the upper part
+        for i in (0..n).rev() {
+            let (cols, values) = self.lu.row(i);
+            let split = cols.partition_point(|j| *j < i);
+            let s: f64 = cols[split + 1..].iter().zip(&values[split + 1..]).map(|(j, uij)| uij * z[*j]).sum();
+            z[i] = (z[i] - s) / values[split];
+        }
+
+        z
+    }
+}
+
+
+/**
+ * Dot product of two sparse vectors given by sorted indices and values
+ */
+fn sparse_dot(indices_a: &[usize], values_a: &[f64], indices_b: &[usize], values_b: &[f64]) -> f64 {
+    let (mut p, mut q) = (0, 0);
+    let mut dot = 0.0;
+
+    while p < indices_a.len() && q < indices_b.len() {
+        match indices_a[p].cmp(&indices_b[q]) {
+            std::cmp::Ordering::Less => p += 1,
+            std::cmp::Ordering::Greater => q += 1,
+            std::cmp::Ordering::Equal => {
+                dot += values_a[p] * values_b[q];
+                p += 1;
+                q += 1;
+            }
+        }
+    }
+
+    dot
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_algebra::exact_methods::{solve_inf, solve_sup};
+    use crate::linear_algebra::helpers::{matmat, transpose};
+    use crate::linear_algebra::iterative_methods::{conjugate_gradient_solver, IterativeOptions};
+
+    /**
+     * 5-point Laplacian on an m x m grid, plus a first order term of weight convection
+     * that makes it nonsymmetric
+     */
+    fn laplacian(m: usize, convection: f64) -> Matrix<f64> {
+        let n = m * m;
+        let mut a = vec![vec![0.0; n]; n];
+
+        for i in 0..n {
+            a[i][i] = 4.0;
+
+            if i % m > 0 {
+                a[i][i - 1] = -1.0 - convection;
+            }
+
+            if i % m + 1 < m {
+                a[i][i + 1] = -1.0 + convection;
+            }
+
+            if i >= m {
+                a[i][i - m] = -1.0;
+            }
+
+            if i + m < n {
+                a[i][i + m] = -1.0;
+            }
+        }
+
+        a
+    }
+
+    /**
+     * Largest |m[i][j] - a[i][j]| over the nonzero entries of A
+     */
+    fn pattern_difference(m: &Matrix<f64>, a: &Matrix<f64>) -> f64 {
+        m.iter()
+            .zip(a)
+            .flat_map(|(m_row, a_row)| m_row.iter().zip(a_row).filter(|(_, aij)| **aij != 0.0).map(|(mij, aij)| f64::abs(mij - aij)))
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn incomplete_cholesky_matches_a_on_its_pattern() {
+        let a = laplacian(5, 0.0);
+        let ic = IncompleteCholeskyPreconditioner::new(&a).expect("the Laplacian is an M-matrix");
+        let g = ic.g.to_dense();
+
+        assert!(pattern_difference(&matmat(&g, &transpose(&g)), &a) < 1e-14);
+        assert!(g.iter().zip(&a).all(|(g_row, a_row)| g_row.iter().zip(a_row).all(|(gij, aij)| *aij != 0.0 || *gij == 0.0)));
+    }
+
+    #[test]
+    fn incomplete_lu_matches_a_on_its_pattern() {
+        let a = laplacian(5, 0.3);
+        let ilu = IncompleteLuPreconditioner::new(&a).expect("the pivots are positive");
+        let lu = ilu.lu.to_dense();
+        let n = a.len();
+
+        let l: Matrix<f64> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else if j < i { lu[i][j] } else { 0.0 }).collect()).collect();
+        let u: Matrix<f64> = (0..n).map(|i| (0..n).map(|j| if j >= i { lu[i][j] } else { 0.0 }).collect()).collect();
+
+        assert!(pattern_difference(&matmat(&l, &u), &a) < 1e-14);
+    }
+
+    #[test]
+    fn incomplete_factorizations_are_exact_without_fill_in() {
+        let a = vec![
+            vec![4.0, -1.0, 0.0, 0.0],
+            vec![-1.0, 4.0, -2.0, 0.0],
+            vec![0.0, -2.0, 5.0, 1.0],
+            vec![0.0, 0.0, 1.0, 3.0],
+        ];
+        let x = [1.0, -2.0, 3.0, 0.5];
+        let b = CsrMatrix::from_dense(&a).spmv(&x);
+
+        let preconditioners: [Box<dyn Preconditioner>; 2] = [
+            Box::new(IncompleteCholeskyPreconditioner::new(&a).unwrap()),
+            Box::new(IncompleteLuPreconditioner::new(&a).unwrap()),
+        ];
+
+        for preconditioner in &preconditioners {
+            let z = preconditioner.apply(&b);
+            assert!(z.iter().zip(&x).all(|(zi, xi)| f64::abs(zi - xi) < 1e-14));
+        }
+    }
+
+    #[test]
+    fn incomplete_cholesky_speeds_up_conjugate_gradient() {
+        let a = CsrMatrix::from_dense(&laplacian(10, 0.0));
+        let b = vec![1.0; a.rows()];
+        let ic = IncompleteCholeskyPreconditioner::from_csr(&a).unwrap();
+
+        let plain = conjugate_gradient_solver(&a, &b, None, &IterativeOptions::default());
+        let preconditioned = conjugate_gradient_solver(&a, &b, Some(&ic), &IterativeOptions::default());
+
+        assert!(plain.converged && preconditioned.converged);
+        assert!(preconditioned.iterations < plain.iterations);
+    }
+
+    #[test]
+    fn incomplete_factorizations_reject_bad_pivots() {
+        let indefinite = vec![vec![1.0, 2.0], vec![2.0, 1.0]];
+        assert!(IncompleteCholeskyPreconditioner::new(&indefinite).is_none());
+
+        let missing_diagonal = vec![vec![0.0, 1.0], vec![1.0, 1.0]];
+        assert!(IncompleteLuPreconditioner::new(&missing_diagonal).is_none());
+    }
+
+    #[test]
+    fn jacobi_from_csr_matches_the_dense_version() {
+        let a = laplacian(4, 0.3);
+        let r: Vec<f64> = (0..a.len()).map(|i| f64::sin(i as f64)).collect();
+
+        let dense = JacobiPreconditioner::new(&a).apply(&r);
+        let sparse = JacobiPreconditioner::from_csr(&CsrMatrix::from_dense(&a)).apply(&r);
+
+        assert_eq!(dense, sparse);
+    }
+
+    #[test]
+    fn ssor_from_csr_matches_the_dense_triangular_solves() {
+        let a = laplacian(4, 0.3);
+        let n = a.len();
+        let omega = 1.3;
+        let r: Vec<f64> = (0..n).map(|i| f64::sin(i as f64)).collect();
+
+        // ( D + omega L ) y = r, ( D + omega U ) z = D y with dense triangles
+        let lower: Matrix<f64> = (0..n).map(|i| (0..n).map(|j| if j < i { omega * a[i][j] } else if j == i { a[i][i] } else { 0.0 }).collect()).collect();
+        let upper: Matrix<f64> = (0..n).map(|i| (0..n).map(|j| if j > i { omega * a[i][j] } else if j == i { a[i][i] } else { 0.0 }).collect()).collect();
+        let y = solve_inf(&lower, &r);
+        let dy: Vec<f64> = y.iter().enumerate().map(|(i, yi)| a[i][i] * yi).collect();
+        let expected: Vec<f64> = solve_sup(&upper, &dy).iter().map(|zi| zi * omega * (2.0 - omega)).collect();
+
+        let sparse = SsorPreconditioner::from_csr(&CsrMatrix::from_dense(&a), omega).apply(&r);
+        let dense = SsorPreconditioner::new(&a, omega).apply(&r);
+
+        for ((s, d), e) in sparse.iter().zip(&dense).zip(&expected) {
+            assert!(f64::abs(s - e) < 1e-14 && f64::abs(d - e) < 1e-14);
+        }
+    }
+
+    #[test]
+    fn csr_preconditioners_speed_up_conjugate_gradient() {
+        let a = CsrMatrix::from_dense(&laplacian(16, 0.0));
+        let b: Vec<f64> = (0..a.rows()).map(|i| f64::sin(i as f64)).collect();
+        let options = IterativeOptions::default();
+
+        let plain = conjugate_gradient_solver(&a, &b, None, &options);
+        let jacobi = conjugate_gradient_solver(&a, &b, Some(&JacobiPreconditioner::from_csr(&a)), &options);
+        let ssor = conjugate_gradient_solver(&a, &b, Some(&SsorPreconditioner::from_csr(&a, 1.5)), &options);
+
+        assert!(plain.converged && jacobi.converged && ssor.converged);
+        assert!(jacobi.iterations <= plain.iterations);
+        assert!(ssor.iterations < plain.iterations);
+    }
+}
@@ -0,0 +1,452 @@
+use crate::types::Matrix;
+use super::iterative_methods::{LinearOperator, RowMatrix};
+
+
+/**
+ * Coordinate (triplet) storage, used to assemble sparse matrices
+ * Entries may be pushed in any order, repeated positions are summed on conversion
+ */
+pub struct CooMatrix {
+    rows: usize,
+    cols: usize,
+    row_indices: Vec<usize>,
+    col_indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+
+/**
+ * Compressed sparse row storage
+ * The entries of row i are values[row_ptr[i]..row_ptr[i + 1]], at the columns
+ * col_indices[row_ptr[i]..row_ptr[i + 1]], sorted in increasing order
+ */
+#[derive(Clone)]
+pub struct CsrMatrix {
+    rows: usize,
+    cols: usize,
+    row_ptr: Vec<usize>,
+    col_indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+
+/**
+ * Compressed sparse column storage
+ * The entries of column j are values[col_ptr[j]..col_ptr[j + 1]], at the rows
+ * row_indices[col_ptr[j]..col_ptr[j + 1]], sorted in increasing order
+ * Row access, used by the stationary solvers, searches every column for the row,
+ * convert large matrices with to_csr before using them
+ */
+pub struct CscMatrix {
+    rows: usize,
+    cols: usize,
+    col_ptr: Vec<usize>,
+    row_indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+
+impl CooMatrix {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self { rows, cols, row_indices: vec![], col_indices: vec![], values: vec![] }
+    }
+
+    /**
+     * Keeps the nonzero entries of a dense matrix
+     */
+    pub fn from_dense(a: &Matrix<f64>) -> Self {
+        let cols = a.first().map_or(0, |row| row.len());
+        let mut coo = Self::new(a.len(), cols);
+
+        for (i, row) in a.iter().enumerate() {
+            for (j, el) in row.iter().enumerate() {
+                if *el != 0.0 {
+                    coo.push(i, j, *el);
+                }
+            }
+        }
+
+        coo
+    }
+
+    /**
+     * Adds value to the entry (i, j)
+     */
+    pub fn push(&mut self, i: usize, j: usize, value: f64) {
+        assert!(i < self.rows && j < self.cols, "entry ({}, {}) is out of a {} x {} matrix", i, j, self.rows, self.cols);
+
+        self.row_indices.push(i);
+        self.col_indices.push(j);
+        self.values.push(value);
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix {
+        let (row_ptr, col_indices, values) = compress(self.rows, &self.row_indices, &self.col_indices, &self.values);
+        CsrMatrix { rows: self.rows, cols: self.cols, row_ptr, col_indices, values }
+    }
+
+    pub fn to_csc(&self) -> CscMatrix {
+        let (col_ptr, row_indices, values) = compress(self.cols, &self.col_indices, &self.row_indices, &self.values);
+        CscMatrix { rows: self.rows, cols: self.cols, col_ptr, row_indices, values }
+    }
+}
+
+
+impl CsrMatrix {
+    pub fn from_dense(a: &Matrix<f64>) -> Self {
+        CooMatrix::from_dense(a).to_csr()
+    }
+
+    /**
+     * Builds the matrix from its arrays, the columns of every row must be sorted and distinct
+     */
+    pub(crate) fn from_parts(rows: usize, cols: usize, row_ptr: Vec<usize>, col_indices: Vec<usize>, values: Vec<f64>) -> Self {
+        Self { rows, cols, row_ptr, col_indices, values }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /**
+     * Returns the sorted column indices and the values of the entries stored in row i
+     */
+    pub(crate) fn row(&self, i: usize) -> (&[usize], &[f64]) {
+        let range = self.row_ptr[i]..self.row_ptr[i + 1];
+        (&self.col_indices[range.clone()], &self.values[range])
+    }
+
+    /**
+     * Returns a[i][j], zero when the entry is not stored
+     */
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        let range = self.row_ptr[i]..self.row_ptr[i + 1];
+
+        match self.col_indices[range.clone()].binary_search(&j) {
+            Ok(k) => self.values[range.start + k],
+            Err(_) => 0.0,
+        }
+    }
+
+    /**
+     * Sparse matrix-vector product y = Ax
+     */
+    pub fn spmv(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.rows)
+            .map(|i| {
+                let range = self.row_ptr[i]..self.row_ptr[i + 1];
+                self.col_indices[range.clone()].iter().zip(&self.values[range]).map(|(j, el)| el * x[*j]).sum()
+            })
+            .collect()
+    }
+
+    /**
+     * The transpose of a CSR matrix has the same arrays as the CSC storage of the matrix
+     */
+    pub fn transpose(&self) -> CsrMatrix {
+        let csc = self.to_csc();
+        CsrMatrix { rows: self.cols, cols: self.rows, row_ptr: csc.col_ptr, col_indices: csc.row_indices, values: csc.values }
+    }
+
+    pub fn to_csc(&self) -> CscMatrix {
+        let row_indices = expand(self.rows, &self.row_ptr);
+        let (col_ptr, row_indices, values) = compress(self.cols, &self.col_indices, &row_indices, &self.values);
+        CscMatrix { rows: self.rows, cols: self.cols, col_ptr, row_indices, values }
+    }
+
+    pub fn to_dense(&self) -> Matrix<f64> {
+        let mut a = vec![vec![0.0; self.cols]; self.rows];
+
+        for (i, row) in a.iter_mut().enumerate() {
+            for k in self.row_ptr[i]..self.row_ptr[i + 1] {
+                row[self.col_indices[k]] = self.values[k];
+            }
+        }
+
+        a
+    }
+}
+
+
+impl CscMatrix {
+    pub fn from_dense(a: &Matrix<f64>) -> Self {
+        CooMatrix::from_dense(a).to_csc()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /**
+     * Returns a[i][j], zero when the entry is not stored
+     */
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        let range = self.col_ptr[j]..self.col_ptr[j + 1];
+
+        match self.row_indices[range.clone()].binary_search(&i) {
+            Ok(k) => self.values[range.start + k],
+            Err(_) => 0.0,
+        }
+    }
+
+    /**
+     * Sparse matrix-vector product y = Ax, accumulating column by column
+     */
+    pub fn spmv(&self, x: &[f64]) -> Vec<f64> {
+        let mut y = vec![0.0; self.rows];
+
+        for (j, xj) in x.iter().enumerate().take(self.cols) {
+            for k in self.col_ptr[j]..self.col_ptr[j + 1] {
+                y[self.row_indices[k]] += self.values[k] * xj;
+            }
+        }
+
+        y
+    }
+
+    /**
+     * The transpose of a CSC matrix has the same arrays as the CSR storage of the matrix
+     */
+    pub fn transpose(&self) -> CscMatrix {
+        let csr = self.to_csr();
+        CscMatrix { rows: self.cols, cols: self.rows, col_ptr: csr.row_ptr, row_indices: csr.col_indices, values: csr.values }
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix {
+        let col_indices = expand(self.cols, &self.col_ptr);
+        let (row_ptr, col_indices, values) = compress(self.rows, &self.row_indices, &col_indices, &self.values);
+        CsrMatrix { rows: self.rows, cols: self.cols, row_ptr, col_indices, values }
+    }
+
+    pub fn to_dense(&self) -> Matrix<f64> {
+        self.to_csr().to_dense()
+    }
+}
+
+
+impl LinearOperator for CsrMatrix {
+    fn dim(&self) -> usize {
+        self.rows
+    }
+
+    fn apply(&self, x: &[f64]) -> Vec<f64> {
+        self.spmv(x)
+    }
+}
+
+impl RowMatrix for CsrMatrix {
+    fn row_entries(&self, i: usize) -> Box<dyn Iterator<Item = (usize, f64)> + '_> {
+        let range = self.row_ptr[i]..self.row_ptr[i + 1];
+        Box::new(self.col_indices[range.clone()].iter().copied().zip(self.values[range].iter().copied()))
+    }
+}
+
+impl LinearOperator for CscMatrix {
+    fn dim(&self) -> usize {
+        self.rows
+    }
+
+    fn apply(&self, x: &[f64]) -> Vec<f64> {
+        self.spmv(x)
+    }
+}
+
+impl RowMatrix for CscMatrix {
+    /**
+     * One binary search per column, O(cols log(nnz / cols)) for every row
+     */
+    fn row_entries(&self, i: usize) -> Box<dyn Iterator<Item = (usize, f64)> + '_> {
+        Box::new((0..self.cols).filter_map(move |j| {
+            let range = self.col_ptr[j]..self.col_ptr[j + 1];
+            let k = self.row_indices[range.clone()].binary_search(&i).ok()?;
+            Some((j, self.values[range.start + k]))
+        }))
+    }
+}
+
+
+/**
+ * Groups the triplets (major[k], minor[k], values[k]) by the major index
+ * Returns the pointer array, the minor indices sorted inside each group and the values,
+ * summing the entries with repeated positions
+ */
+fn compress(major_dim: usize, major: &[usize], minor: &[usize], values: &[f64]) -> (Vec<usize>, Vec<usize>, Vec<f64>) {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|k| (major[*k], minor[*k]));
+
+    let mut ptr = vec![0; major_dim + 1];
+    let mut indices: Vec<usize> = Vec::with_capacity(values.len());
+    let mut compressed: Vec<f64> = Vec::with_capacity(values.len());
+    let mut last: Option<(usize, usize)> = None;
+
+    for k in order {
+        if last == Some((major[k], minor[k])) {
+            if let Some(value) = compressed.last_mut() {
+                *value += values[k];
+            }
+            continue;
+        }
+
+        last = Some((major[k], minor[k]));
+        ptr[major[k] + 1] += 1;
+        indices.push(minor[k]);
+        compressed.push(values[k]);
+    }
+
+    for i in 0..major_dim {
+        ptr[i + 1] += ptr[i];
+    }
+
+    (ptr, indices, compressed)
+}
+
+
+/**
+ * Inverse of the pointer array: returns the major index of every stored entry
+ */
+fn expand(major_dim: usize, ptr: &[usize]) -> Vec<usize> {
+    let mut major = Vec::with_capacity(ptr[major_dim]);
+
+    for i in 0..major_dim {
+        major.extend(std::iter::repeat_n(i, ptr[i + 1] - ptr[i]));
+    }
+
+    major
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_algebra::helpers::{matvec, transpose};
+    use crate::linear_algebra::iterative_methods::{gauss_seidel_solver, jacobi_richardson_solver, sor_solver, ssor_solver, IterativeOptions};
+
+    fn example() -> Matrix<f64> {
+        vec![
+            vec![4.0, 0.0, -1.0, 0.0, 0.5],
+            vec![0.0, 5.0, 0.0, 2.0, 0.0],
+            vec![-1.0, 0.0, 6.0, 0.0, 0.0],
+            vec![0.0, 2.0, 0.0, 7.0, -3.0],
+        ]
+    }
+
+    #[test]
+    fn coo_conversions_sum_repeated_entries() {
+        let mut coo = CooMatrix::new(3, 4);
+        coo.push(2, 3, 1.0);
+        coo.push(0, 1, 2.0);
+        coo.push(2, 3, 4.0);
+        coo.push(1, 0, -1.0);
+        coo.push(0, 1, 0.5);
+        coo.push(2, 0, 3.0);
+
+        let expected = vec![
+            vec![0.0, 2.5, 0.0, 0.0],
+            vec![-1.0, 0.0, 0.0, 0.0],
+            vec![3.0, 0.0, 0.0, 5.0],
+        ];
+
+        assert_eq!(coo.nnz(), 6);
+
+        let csr = coo.to_csr();
+        let csc = coo.to_csc();
+
+        assert_eq!(csr.nnz(), 4);
+        assert_eq!(csc.nnz(), 4);
+        assert_eq!(csr.to_dense(), expected);
+        assert_eq!(csc.to_dense(), expected);
+        assert_eq!((csr.get(2, 3), csr.get(1, 1)), (5.0, 0.0));
+        assert_eq!((csc.get(0, 1), csc.get(2, 2)), (2.5, 0.0));
+    }
+
+    #[test]
+    fn spmv_matches_the_dense_product() {
+        let a = example();
+        let x = vec![1.0, -2.0, 0.5, 3.0, -1.5];
+        let expected = matvec(&a, &x);
+
+        assert_eq!(CsrMatrix::from_dense(&a).spmv(&x), expected);
+        assert_eq!(CscMatrix::from_dense(&a).spmv(&x), expected);
+    }
+
+    #[test]
+    fn transposes_and_format_conversions_round_trip() {
+        let a = example();
+        let csr = CsrMatrix::from_dense(&a);
+        let csc = CscMatrix::from_dense(&a);
+
+        assert_eq!(csr.transpose().to_dense(), transpose(&a));
+        assert_eq!(csc.transpose().to_dense(), transpose(&a));
+        assert_eq!(csr.transpose().transpose().to_dense(), a);
+        assert_eq!(csc.transpose().transpose().to_dense(), a);
+        assert_eq!(csr.to_csc().to_csr().to_dense(), a);
+        assert_eq!(csc.to_csr().to_csc().to_dense(), a);
+        assert_eq!((csr.transpose().rows(), csr.transpose().cols()), (5, 4));
+    }
+
+    #[test]
+    fn row_entries_agree_between_formats() {
+        let a = example();
+        let csr = CsrMatrix::from_dense(&a);
+        let csc = CscMatrix::from_dense(&a);
+
+        for (i, row) in a.iter().enumerate() {
+            let expected: Vec<(usize, f64)> = row.iter().copied().enumerate().filter(|(_, el)| *el != 0.0).collect();
+
+            assert_eq!(csr.row_entries(i).collect::<Vec<_>>(), expected);
+            assert_eq!(csc.row_entries(i).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn stationary_solvers_accept_every_storage() {
+        let a = vec![
+            vec![4.0, -1.0, 0.0, -1.0],
+            vec![-1.0, 4.0, -1.0, 0.0],
+            vec![0.0, -1.0, 4.0, -1.0],
+            vec![-1.0, 0.0, -1.0, 4.0],
+        ];
+        let x = vec![1.0, 2.0, -1.0, 0.5];
+        let b = matvec(&a, &x);
+        let csr = CsrMatrix::from_dense(&a);
+        let csc = CscMatrix::from_dense(&a);
+        let options = IterativeOptions::default();
+
+        let reports = [
+            jacobi_richardson_solver(&csr, &b, &options),
+            jacobi_richardson_solver(&csc, &b, &options),
+            gauss_seidel_solver(&csc, &b, &options),
+            sor_solver(&csr, &b, None, &options),
+            sor_solver(&csc, &b, Some(1.2), &options),
+            ssor_solver(&csr, &b, None, &options),
+            ssor_solver(&csc, &b, Some(1.2), &options),
+        ];
+
+        for report in reports {
+            assert!(report.converged && report.convergence_guaranteed);
+            assert!(report.solution.iter().zip(&x).all(|(xi, expected)| f64::abs(xi - expected) < 1e-9));
+        }
+
+        assert_eq!(sor_solver(&csc, &b, Some(1.2), &options).iterations, sor_solver(&a, &b, Some(1.2), &options).iterations);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::types::Matrix;
+use super::eigen::rotate_columns;
+use super::helpers::{dot, get_identity_matrix, transpose, vecnorm};
+
+
+/**
+ * Singular value decomposition A = U diag(singular_values) V^T of a m x n matrix
+ * singular_values are sorted in decreasing order
+ * Thin: U is m x k, V is n x k, k = min(m, n)
+ * Full: U is m x m, V is n x n and only the first k singular values exist
+ * U and V have orthonormal columns
+ */
+pub struct Svd {
+    pub u: Matrix<f64>,
+    pub singular_values: Vec<f64>,
+    pub v: Matrix<f64>,
+}
+
+
+/**
+ * Thin singular value decomposition with the one-sided Jacobi method (Hestenes)
+ * Columns p and q of A V are rotated until they are orthogonal, then
+ * sigma[j] = ||(A V)[j]|| and U[j] = (A V)[j] / sigma[j]
+ * Works on A^T when m < n
+ */
+pub fn svd(a: &Matrix<f64>) -> Svd {
+    let m = a.len();
+    let n = a.first().map_or(0, |row| row.len());
+
+    if m < n {
+        let Svd { u, singular_values, v } = svd(&transpose(a));
+        return Svd { u: v, singular_values, v: u };
+    }
+
+    let mut w = a.clone(); // becomes A V
+    let mut v = get_identity_matrix(n);
+
+    for _ in 0..MAX_SWEEPS {
+        let mut rotated = false;
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                let alpha: f64 = w.iter().map(|row| row[p] * row[p]).sum();
+                let beta: f64 = w.iter().map(|row| row[q] * row[q]).sum();
+                let gamma: f64 = w.iter().map(|row| row[p] * row[q]).sum();
+
+                if f64::abs(gamma) <= f64::EPSILON * f64::sqrt(alpha * beta) || gamma == 0. {
+                    continue;
+                }
+
+                rotated = true;
+
+                let zeta = (beta - alpha) / (2. * gamma);
+                let t = 1f64.copysign(zeta) / (f64::abs(zeta) + f64::hypot(zeta, 1.));
+                let c = 1. / f64::hypot(t, 1.);
+                let s = c * t;
+
+                rotate_columns(&mut w, p, q, c, s);
+                rotate_columns(&mut v, p, q, c, s);
+            }
+        }
+
+        if !rotated {
+            break;
+        }
+    }
+
+    let w_columns = transpose_keeping_rows(&w, n);
+    let v_columns = transpose_keeping_rows(&v, n);
+    let sigma: Vec<f64> = w_columns.iter().map(|col| vecnorm(col)).collect();
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|i, j| sigma[*j].total_cmp(&sigma[*i]));
+
+    let singular_values: Vec<f64> = order.iter().map(|j| sigma[*j]).collect();
+    let negligible = f64::EPSILON * m as f64 * singular_values.first().copied().unwrap_or(0.);
+
+    // the columns of zero singular values are completed to an orthonormal set
+    let mut u_columns: Vec<Vec<f64>> = order
+        .iter()
+        .filter(|j| sigma[**j] > negligible)
+        .map(|j| w_columns[*j].iter().map(|el| el / sigma[*j]).collect())
+        .collect();
+    complete_orthonormal_columns(&mut u_columns, m, n);
+
+    let v_columns: Vec<Vec<f64>> = order.iter().map(|j| v_columns[*j].clone()).collect();
+
+    Svd {
+        u: transpose_keeping_rows(&u_columns, m),
+        singular_values,
+        v: transpose_keeping_rows(&v_columns, n),
+    }
+}
+
+
+/**
+ * Full singular value decomposition, the thin one with U or V completed to square orthogonal matrices
+ */
+pub fn full_svd(a: &Matrix<f64>) -> Svd {
+    let m = a.len();
+    let n = a.first().map_or(0, |row| row.len());
+    let Svd { u, singular_values, v } = svd(a);
+
+    let mut u_columns = transpose_keeping_rows(&u, singular_values.len());
+    let mut v_columns = transpose_keeping_rows(&v, singular_values.len());
+    complete_orthonormal_columns(&mut u_columns, m, m);
+    complete_orthonormal_columns(&mut v_columns, n, n);
+
+    Svd {
+        u: transpose_keeping_rows(&u_columns, m),
+        singular_values,
+        v: transpose_keeping_rows(&v_columns, n),
+    }
+}
+
+
+/**
+ * Condition number in the 2-norm, cond(A) = sigma_max / sigma_min
+ * Infinite for singular matrices
+ */
+pub fn cond_2(a: &Matrix<f64>) -> f64 {
+    let s = svd(a).singular_values;
+
+    match (s.first(), s.last()) {
+        (Some(max), Some(min)) if *min > 0. => max / min,
+        _ => f64::INFINITY,
+    }
+}
+
+
+/**
+ * Numerical rank, the number of singular values greater than tolerance
+ * tolerance defaults to max(m, n) * eps * sigma_max
+ */
+pub fn rank(a: &Matrix<f64>, tolerance: Option<f64>) -> usize {
+    let s = svd(a).singular_values;
+    let tolerance = tolerance.unwrap_or_else(|| default_tolerance(a, &s));
+
+    s.iter().filter(|sigma| **sigma > tolerance).count()
+}
+
+
+/**
+ * Moore-Penrose pseudo-inverse, A+ = V diag(1 / sigma) U^T, a n x m matrix
+ * Singular values not greater than tolerance are treated as zero,
+ * tolerance defaults to max(m, n) * eps * sigma_max
+ */
+pub fn pseudo_inverse(a: &Matrix<f64>, tolerance: Option<f64>) -> Matrix<f64> {
+    let Svd { u, singular_values, v } = svd(a);
+    let tolerance = tolerance.unwrap_or_else(|| default_tolerance(a, &singular_values));
+    let inverse_s: Vec<f64> = singular_values.iter().map(|s| if *s > tolerance { 1. / s } else { 0. }).collect();
+
+    v.iter()
+        .map(|v_row| u.iter().map(|u_row| (0..inverse_s.len()).map(|k| v_row[k] * inverse_s[k] * u_row[k]).sum()).collect())
+        .collect()
+}
+
+
+/**
+ * Best rank k approximation of A in the 2 and Frobenius norms (Eckart-Young),
+ * A_k = sum(sigma[i] u[i] v[i]^T, i < k)
+ */
+pub fn low_rank_approximation(a: &Matrix<f64>, k: usize) -> Matrix<f64> {
+    let Svd { u, singular_values, v } = svd(a);
+    let k = usize::min(k, singular_values.len());
+
+    u.iter()
+        .map(|u_row| v.iter().map(|v_row| (0..k).map(|i| u_row[i] * singular_values[i] * v_row[i]).sum()).collect())
+        .collect()
+}
+
+
+const MAX_SWEEPS: usize = 100;
+
+
+fn default_tolerance(a: &Matrix<f64>, singular_values: &[f64]) -> f64 {
+    let m = a.len();
+    let n = a.first().map_or(0, |row| row.len());
+    usize::max(m, n) as f64 * f64::EPSILON * singular_values.first().copied().unwrap_or(0.)
+}
+
+
+/**
+ * Adds unit vectors of dimension m orthogonal to the given columns until there are count columns
+ * Each new column is the canonical basis vector with the largest component outside the current span,
+ * orthogonalized with the modified Gram-Schmidt process
+ */
+fn complete_orthonormal_columns(columns: &mut Vec<Vec<f64>>, m: usize, count: usize) {
+    while columns.len() < count.min(m) {
+        let best = (0..m)
+            .map(|i| {
+                let mut e = vec![0.; m];
+                e[i] = 1.;
+
+                // twice is enough to orthogonalize in floating point
+                for _ in 0..2 {
+                    for col in columns.iter() {
+                        let projection = dot(&e, col);
+
+                        for (ei, ci) in e.iter_mut().zip(col) {
+                            *ei -= projection * ci;
+                        }
+                    }
+                }
+
+                e
+            })
+            .max_by(|e, f| vecnorm(e).total_cmp(&vecnorm(f)));
+
+        match best {
+            Some(e) => {
+                let norm = vecnorm(&e);
+                columns.push(e.iter().map(|ei| ei / norm).collect());
+            }
+            None => break,
+        }
+    }
+}
+
+
+/**
+ * Transposes a, returning result_rows empty rows when a has no entries
+ */
+fn transpose_keeping_rows(a: &Matrix<f64>, result_rows: usize) -> Matrix<f64> {
+    if a.is_empty() || a[0].is_empty() {
+        return vec![vec![]; result_rows];
+    }
+
+    transpose(a)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_algebra::helpers::{infinity_norm, matmat};
+
+    fn max_abs_difference(a: &Matrix<f64>, b: &Matrix<f64>) -> f64 {
+        a.iter()
+            .zip(b)
+            .flat_map(|(row_a, row_b)| row_a.iter().zip(row_b).map(|(x, y)| f64::abs(x - y)))
+            .fold(0., f64::max)
+    }
+
+    /**
+     * U diag(singular_values) V^T, with only the first singular_values.len() columns of U and V
+     */
+    fn reconstruct(svd: &Svd) -> Matrix<f64> {
+        let k = svd.singular_values.len();
+
+        svd.u.iter()
+            .map(|u_row| svd.v.iter().map(|v_row| (0..k).map(|i| u_row[i] * svd.singular_values[i] * v_row[i]).sum()).collect())
+            .collect()
+    }
+
+    fn assert_orthonormal_columns(q: &Matrix<f64>) {
+        let columns = q.first().map_or(0, |row| row.len());
+        assert!(max_abs_difference(&matmat(&transpose(q), q), &get_identity_matrix(columns)) < 1e-13);
+    }
+
+    #[test]
+    fn svd_reconstructs_tall_and_wide_matrices() {
+        let tall = vec![
+            vec![1., 2., 3.],
+            vec![4., 5., 6.],
+            vec![7., 8., 10.],
+            vec![-1., 0., 2.],
+        ];
+
+        for a in [tall.clone(), transpose(&tall)] {
+            let decomposition = svd(&a);
+
+            assert_eq!(decomposition.singular_values.len(), 3);
+            assert!(decomposition.singular_values.windows(2).all(|pair| pair[0] >= pair[1]));
+            assert_orthonormal_columns(&decomposition.u);
+            assert_orthonormal_columns(&decomposition.v);
+            assert!(max_abs_difference(&reconstruct(&decomposition), &a) < 1e-13 * infinity_norm(&a));
+
+            let full = full_svd(&a);
+
+            assert_eq!((full.u.len(), full.u[0].len()), (a.len(), a.len()));
+            assert_eq!((full.v.len(), full.v[0].len()), (a[0].len(), a[0].len()));
+            assert_orthonormal_columns(&full.u);
+            assert_orthonormal_columns(&full.v);
+            assert!(max_abs_difference(&reconstruct(&full), &a) < 1e-13 * infinity_norm(&a));
+        }
+    }
+
+    #[test]
+    fn pseudo_inverse_satisfies_the_moore_penrose_conditions() {
+        // rank 2: the third column is the sum of the first two
+        let a = vec![
+            vec![1., 2., 3.],
+            vec![4., 5., 9.],
+            vec![7., 8., 15.],
+            vec![-1., 0., -1.],
+        ];
+        let a_plus = pseudo_inverse(&a, None);
+        let tolerance = 1e-12;
+
+        assert_eq!(rank(&a, None), 2);
+        assert_eq!((a_plus.len(), a_plus[0].len()), (3, 4));
+
+        let a_a_plus = matmat(&a, &a_plus);
+        let a_plus_a = matmat(&a_plus, &a);
+
+        // A A+ A = A, A+ A A+ = A+, (A A+)^T = A A+, (A+ A)^T = A+ A
+        assert!(max_abs_difference(&matmat(&a_a_plus, &a), &a) < tolerance * infinity_norm(&a));
+        assert!(max_abs_difference(&matmat(&a_plus_a, &a_plus), &a_plus) < tolerance * infinity_norm(&a_plus));
+        assert!(max_abs_difference(&transpose(&a_a_plus), &a_a_plus) < tolerance);
+        assert!(max_abs_difference(&transpose(&a_plus_a), &a_plus_a) < tolerance);
+    }
+
+    #[test]
+    fn condition_number_of_a_diagonal_matrix() {
+        let a = vec![vec![3., 0.], vec![0., -0.5]];
+
+        assert!(f64::abs(cond_2(&a) - 6.) < 1e-14);
+        assert_eq!(cond_2(&vec![vec![1., 2.], vec![2., 4.]]), f64::INFINITY);
+    }
+}
@@ -1 +1,27 @@
 pub type Matrix<T> = Vec<Vec<T>>;
+pub type VectorFunction<'a> = dyn Fn(&[f64]) -> Vec<f64> + 'a;
+pub type MatrixFunction<'a> = dyn Fn(&[f64]) -> Matrix<f64> + 'a;
+pub type ScalarFunction<'a> = dyn Fn(&[f64]) -> f64 + 'a;
+
+
+/**
+ * Polynomial with real coefficients in decreasing powers
+ * p(x) = coefficients[0] x^n + coefficients[1] x^(n-1) + ... + coefficients[n]
+ */
+#[derive(Clone, Debug)]
+pub struct Polynomial {
+    pub coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    /**
+     * Evaluates p(x) with the Horner scheme
+     */
+    pub fn eval(&self, x: f64) -> f64 {
+        self.coefficients.iter().fold(0., |acc, c| acc * x + c)
+    }
+}